@@ -1,5 +1,5 @@
 #[allow(dead_code)]
-const SOURCE_CODE: &'static str = r###"
+const SOURCE_CODE: &str = r###"
 # Hello World
 ## Hello again
 
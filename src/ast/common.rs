@@ -1,13 +1,72 @@
 use std::convert::TryFrom;
 
+/// Key/value pairs from the front matter at the top of a document.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Metadata<'a> {
+    /// Entries in source order.
+    pub entries: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Metadata<'a> {
+    /// Returns the value of `key`. Later entries override earlier ones.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(key_, _)| *key_ == key)
+            .map(|(_, value)| *value)
+    }
+
+    pub fn title(&self) -> Option<&'a str> {
+        self.get("title")
+    }
+
+    pub fn author(&self) -> Option<&'a str> {
+        self.get("author")
+    }
+
+    pub fn date(&self) -> Option<&'a str> {
+        self.get("date")
+    }
+
+    pub fn lang(&self) -> Option<&'a str> {
+        self.get("lang")
+    }
+
+    /// Returns the tags, written either as `[a, b]` or as `a, b`.
+    pub fn tags(&self) -> Vec<&'a str> {
+        let tags = self.get("tags").unwrap_or_default();
+        let tags = tags
+            .strip_prefix('[')
+            .and_then(|tags| tags.strip_suffix(']'))
+            .unwrap_or(tags);
+
+        tags.split(',')
+            .map(|tag| tag.trim().trim_matches('"'))
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Emphasis {
     Bold,
     Italic,
     Strikethrough,
+    Underline,
+    Mark,
+    Superscript,
+    Subscript,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub enum HeaderType {
     H1,
     H2,
@@ -32,3 +91,21 @@ impl TryFrom<u32> for HeaderType {
         }
     }
 }
+
+/// Written as `{#id .class key=value}`.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Attributes<'a> {
+    pub id: Option<&'a str>,
+    pub classes: Vec<&'a str>,
+    /// Other attributes in source order.
+    pub pairs: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Attributes<'a> {
+    /// Adds `other` to these attributes, with its ID taking precedence.
+    pub fn extend(&mut self, other: &Attributes<'a>) {
+        self.id = other.id.or(self.id);
+        self.classes.extend(&other.classes);
+        self.pairs.extend(&other.pairs);
+    }
+}
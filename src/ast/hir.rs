@@ -1,8 +1,21 @@
 pub use super::common::*;
+pub use crate::bibliography::{CitationStyle, Entry};
+use std::borrow::Cow;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Document<'a> {
+    pub metadata: Metadata<'a>,
     pub statements: Vec<Statement<'a>>,
+    /// Footnotes ordered by their number, starting at 1.
+    pub footnotes: Vec<Footnote<'a>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Footnote<'a> {
+    pub label: &'a str,
+    pub text: Text<'a>,
+    /// How often the footnote is referenced in the document.
+    pub references: usize,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -11,36 +24,149 @@ pub enum Statement<'a> {
     Paragraph(ParagraphStatement<'a>),
     List(ListStatement<'a>),
     HorizontalRule(HorizontalRuleStatement),
+    Figure(FigureStatement<'a>),
+    CodeBlock(CodeBlockStatement<'a>),
+    Raw(RawStatement<'a>),
+    MathBlock(MathBlockStatement<'a>),
+    BlockQuote(BlockQuoteStatement<'a>),
+    Table(TableStatement<'a>),
+    DefinitionList(DefinitionListStatement<'a>),
+    Admonition(AdmonitionStatement<'a>),
+    TableOfContents(TableOfContentsStatement<'a>),
+    Bibliography(BibliographyStatement),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct HeaderStatement<'a> {
     pub header_type: HeaderType,
+    /// Explicit ID or one generated from the text, unique within the document.
+    pub id: String,
+    /// Attributes besides the ID.
+    pub attributes: Attributes<'a>,
     pub text: Text<'a>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ParagraphStatement<'a> {
+    pub attributes: Attributes<'a>,
     pub text: Text<'a>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ListStatement<'a> {
+    pub attributes: Attributes<'a>,
     pub list: List<'a>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct HorizontalRuleStatement;
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FigureStatement<'a> {
+    pub image: Image<'a>,
+    pub caption: Text<'a>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CodeBlockStatement<'a> {
+    pub info: Option<&'a str>,
+    pub content: Cow<'a, str>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RawStatement<'a> {
+    /// Name of the backend that outputs the content verbatim. Other backends
+    /// drop it.
+    pub target: &'a str,
+    pub content: Cow<'a, str>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MathBlockStatement<'a> {
+    /// TeX source of the formula.
+    pub content: Cow<'a, str>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BlockQuoteStatement<'a> {
+    pub statements: Vec<Statement<'a>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TableOfContentsStatement<'a> {
+    pub min_level: HeaderType,
+    pub max_level: HeaderType,
+    /// Cross-references to the headers within the levels, nested by level.
+    pub list: List<'a>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BibliographyStatement {
+    pub style: CitationStyle,
+    /// Cited entries, numbered in citation order or sorted by author.
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AdmonitionStatement<'a> {
+    pub kind: AdmonitionKind,
+    pub attributes: Attributes<'a>,
+    pub title: Option<Text<'a>>,
+    pub statements: Vec<Statement<'a>>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AdmonitionKind {
+    Note,
+    Tip,
+    Warning,
+    Danger,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TableStatement<'a> {
+    pub alignments: Vec<Option<Alignment>>,
+    pub header: Option<TableRow<'a>>,
+    pub rows: Vec<TableRow<'a>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TableRow<'a> {
+    pub cells: Vec<Text<'a>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DefinitionListStatement<'a> {
+    pub attributes: Attributes<'a>,
+    pub items: Vec<DefinitionItem<'a>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DefinitionItem<'a> {
+    /// Empty for definitions without a preceding term.
+    pub term: Text<'a>,
+    pub definitions: Vec<Text<'a>>,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct List<'a> {
+    pub list_type: ListType,
     pub items: Vec<ListItem<'a>>,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ListType {
+    Unordered,
+    Ordered { start: u32 },
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ListItem<'a> {
+    /// `Some` for task items, telling whether the task is done.
+    pub checked: Option<bool>,
     pub text: Text<'a>,
-    pub child: Option<List<'a>>,
+    /// Nested lists; a new one starts wherever the marker type changes.
+    pub children: Vec<List<'a>>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -50,10 +176,57 @@ pub struct Text<'a> {
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum TextSegment<'a> {
-    Text(&'a str),
+    Text(Cow<'a, str>),
     Break,
     Emphasised {
         emphasis: Emphasis,
         inner: Vec<TextSegment<'a>>,
     },
+    Link {
+        label: Vec<TextSegment<'a>>,
+        url: &'a str,
+        title: Option<&'a str>,
+    },
+    Image(Image<'a>),
+    Code(&'a str),
+    Math(&'a str),
+    CrossReference {
+        id: Cow<'a, str>,
+        /// Text of the referenced header.
+        text: Vec<TextSegment<'a>>,
+    },
+    FootnoteReference {
+        number: usize,
+        /// Counts the references to the same footnote, starting at 1.
+        occurrence: usize,
+    },
+    Citation {
+        style: CitationStyle,
+        items: Vec<CitationItem<'a>>,
+    },
+    Span {
+        inner: Vec<TextSegment<'a>>,
+        attributes: Attributes<'a>,
+    },
+    /// Output for the backend named `target`, which other backends drop.
+    Raw {
+        target: &'a str,
+        content: Cow<'a, str>,
+    },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CitationItem<'a> {
+    /// Counts the cited entries in order of their first citation, starting at 1.
+    pub number: usize,
+    pub entry: Entry,
+    /// Part of the work that is cited, like `p. 12`.
+    pub locator: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Image<'a> {
+    pub alt: &'a str,
+    pub url: &'a str,
+    pub title: Option<&'a str>,
 }
@@ -1,7 +1,9 @@
 pub use super::common::*;
+use std::borrow::Cow;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Document<'a> {
+    pub metadata: Metadata<'a>,
     pub statements: Vec<Statement<'a>>,
 }
 
@@ -13,6 +15,25 @@ pub enum Statement<'a> {
     Paragraph(ParagraphStatement<'a>),
     ListItem(ListItemStatement<'a>),
     HorizontalRule(HorizontalRuleStatement),
+    Figure(FigureStatement<'a>),
+    CodeBlock(CodeBlockStatement<'a>),
+    Raw(RawStatement<'a>),
+    MathBlock(MathBlockStatement<'a>),
+    BlockQuote(BlockQuoteStatement<'a>),
+    TableRow(TableRowStatement<'a>),
+    TableSeparator(TableSeparatorStatement),
+    FootnoteDefinition(FootnoteDefinitionStatement<'a>),
+    Definition(DefinitionStatement<'a>),
+    AdmonitionStart(AdmonitionStartStatement<'a>),
+    AdmonitionEnd(AdmonitionEndStatement),
+    Directive(DirectiveStatement<'a>),
+    Conditional(ConditionalStatement<'a>),
+    Include(IncludeStatement<'a>),
+    VariableDefinition(VariableDefinitionStatement<'a>),
+    TableOfContents(TableOfContentsStatement),
+    Bibliography(BibliographyStatement),
+    /// Attributes of the following header, paragraph or list.
+    Attributes(AttributesStatement<'a>),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -20,12 +41,13 @@ pub struct EmptyLineStatement;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct CommentStatement<'a> {
-    pub text: &'a str,
+    pub text: Cow<'a, str>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct HeaderStatement<'a> {
     pub header_type: HeaderType,
+    pub attributes: Attributes<'a>,
     pub text: Text<'a>,
 }
 
@@ -37,12 +59,145 @@ pub struct ParagraphStatement<'a> {
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ListItemStatement<'a> {
     pub indentation: usize,
+    pub marker: ListMarker,
+    /// `Some` for task items, telling whether the task is done.
+    pub checked: Option<bool>,
     pub text: Text<'a>,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ListMarker {
+    Bullet,
+    Number(u32),
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct HorizontalRuleStatement;
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FigureStatement<'a> {
+    pub image: Image<'a>,
+    pub caption: Text<'a>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CodeBlockStatement<'a> {
+    pub info: Option<&'a str>,
+    pub content: Cow<'a, str>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RawStatement<'a> {
+    /// Name of the backend that outputs the content.
+    pub target: &'a str,
+    pub content: Cow<'a, str>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MathBlockStatement<'a> {
+    /// TeX source of the formula.
+    pub content: Cow<'a, str>,
+}
+
+/// A statement whose lines all start with `>`. Consecutive ones form a quote.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BlockQuoteStatement<'a> {
+    pub statement: Box<Statement<'a>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TableRowStatement<'a> {
+    pub cells: Vec<Text<'a>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TableSeparatorStatement {
+    pub alignments: Vec<Option<Alignment>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FootnoteDefinitionStatement<'a> {
+    pub label: &'a str,
+    pub text: Text<'a>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DefinitionStatement<'a> {
+    pub text: Text<'a>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VariableDefinitionStatement<'a> {
+    pub name: &'a str,
+    pub value: &'a str,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TableOfContentsStatement {
+    /// Shallowest header level to list.
+    pub min_level: HeaderType,
+    /// Deepest header level to list.
+    pub max_level: HeaderType,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BibliographyStatement;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AttributesStatement<'a> {
+    pub attributes: Attributes<'a>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct IncludeStatement<'a> {
+    /// Path of the included file, relative to the including one.
+    pub path: &'a str,
+}
+
+/// Opens an admonition or a custom container directive, whose name is the
+/// kind. The statements up to the matching [`AdmonitionEndStatement`] form its
+/// body.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AdmonitionStartStatement<'a> {
+    pub kind: &'a str,
+    pub arguments: Attributes<'a>,
+    pub title: Option<Text<'a>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AdmonitionEndStatement;
+
+/// A leaf directive.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DirectiveStatement<'a> {
+    pub name: &'a str,
+    pub label: Vec<TextSegment<'a>>,
+    pub arguments: Attributes<'a>,
+}
+
+/// A line of an `@if` block. The HIR parser pairs them up.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ConditionalStatement<'a> {
+    If(Condition<'a>),
+    /// Branch taken when no earlier condition of the block holds.
+    Elif(Condition<'a>),
+    Else,
+    End,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Condition<'a> {
+    /// Holds when the flag is neither empty nor `false`.
+    Flag(&'a str),
+    /// `flag == "value"`.
+    Equal(&'a str, &'a str),
+    /// `flag != "value"`.
+    NotEqual(&'a str, &'a str),
+    Not(Box<Condition<'a>>),
+    And(Box<Condition<'a>>, Box<Condition<'a>>),
+    Or(Box<Condition<'a>>, Box<Condition<'a>>),
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Text<'a> {
     pub segments: Vec<TextSegment<'a>>,
@@ -50,7 +205,44 @@ pub struct Text<'a> {
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum TextSegment<'a> {
-    Text(&'a str),
+    Text(Cow<'a, str>),
     Break,
     Emphasis(Emphasis),
+    Link {
+        label: Vec<TextSegment<'a>>,
+        url: &'a str,
+        title: Option<&'a str>,
+    },
+    Image(Image<'a>),
+    Code(&'a str),
+    Math(&'a str),
+    /// Name of a variable to substitute.
+    Variable(&'a str),
+    /// ID of the header that is referenced.
+    CrossReference(&'a str),
+    FootnoteReference(&'a str),
+    Citation(Vec<Citation<'a>>),
+    Span {
+        text: Vec<TextSegment<'a>>,
+        attributes: Attributes<'a>,
+    },
+    Directive {
+        name: &'a str,
+        label: Vec<TextSegment<'a>>,
+        arguments: Attributes<'a>,
+    },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Citation<'a> {
+    pub key: &'a str,
+    /// Part of the work that is cited, like `p. 12`.
+    pub locator: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Image<'a> {
+    pub alt: &'a str,
+    pub url: &'a str,
+    pub title: Option<&'a str>,
 }
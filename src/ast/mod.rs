@@ -2,3 +2,5 @@ mod common;
 
 pub mod hir;
 pub mod lir;
+
+pub use self::common::Metadata;
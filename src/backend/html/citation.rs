@@ -0,0 +1,167 @@
+use crate::ast::hir::{self, CitationStyle, Entry};
+
+pub(super) fn generate_citation(
+    style: CitationStyle,
+    items: &[hir::CitationItem<'_>],
+    output: &mut String,
+) {
+    *output += r#"<span class="writer4-citation">"#;
+    *output += match style {
+        CitationStyle::AuthorYear => "(",
+        CitationStyle::Numeric => "[",
+    };
+    for (idx, item) in items.iter().enumerate() {
+        if idx > 0 {
+            *output += "; ";
+        }
+        *output += &format!(r##"<a href="#{}">"##, reference_id(&item.entry));
+        match style {
+            CitationStyle::AuthorYear => {
+                *output += &html_escape::encode_text(&short_authors(&item.entry));
+                *output += " ";
+                *output += &html_escape::encode_text(year(&item.entry));
+            }
+            CitationStyle::Numeric => *output += &item.number.to_string(),
+        }
+        *output += "</a>";
+        if let Some(locator) = item.locator {
+            *output += ", ";
+            *output += &html_escape::encode_text(locator);
+        }
+    }
+    *output += match style {
+        CitationStyle::AuthorYear => ")",
+        CitationStyle::Numeric => "]",
+    };
+    *output += "</span>";
+}
+
+pub(super) fn generate_bibliography(style: CitationStyle, entries: &[Entry], output: &mut String) {
+    let tag = match style {
+        CitationStyle::AuthorYear => "ul",
+        CitationStyle::Numeric => "ol",
+    };
+
+    *output += &format!(r#"<section class="writer4-bibliography"><{}>"#, tag);
+    for entry in entries {
+        *output += &format!(r#"<li id="{}">"#, reference_id(entry));
+        generate_reference(style, entry, output);
+        *output += "</li>";
+    }
+    *output += &format!("</{}></section>", tag);
+}
+
+/// Formats an entry like `Doe, Jane and Richard Roe (2020). Title. <i>Journal</i>, 3, 1–10.`
+/// with the year moving to the end for numeric styles.
+fn generate_reference(style: CitationStyle, entry: &Entry, output: &mut String) {
+    let mut title = entry.title.as_deref();
+
+    // Works without authors are listed by their title
+    let lead = match (full_authors(entry), style) {
+        (Some(authors), _) => Some(authors),
+        (None, CitationStyle::AuthorYear) => title.take().map(str::to_string),
+        (None, CitationStyle::Numeric) => None,
+    };
+    match style {
+        CitationStyle::AuthorYear => {
+            *output += &html_escape::encode_text(lead.as_deref().unwrap_or(&entry.key));
+            *output += &format!(" ({}). ", html_escape::encode_text(year(entry)));
+        }
+        CitationStyle::Numeric => {
+            if let Some(lead) = lead {
+                push_sentence(&lead, output);
+            }
+        }
+    }
+
+    if let Some(title) = title {
+        push_sentence(title, output);
+    }
+
+    if let Some(container_title) = &entry.container_title {
+        *output += "<i>";
+        *output += &html_escape::encode_text(container_title);
+        *output += "</i>";
+        for part in entry.volume.iter().chain(&entry.pages) {
+            *output += ", ";
+            *output += &html_escape::encode_text(part);
+        }
+        *output += ". ";
+    }
+
+    match (&entry.publisher, style) {
+        (Some(publisher), CitationStyle::Numeric) => {
+            push_sentence(&format!("{}, {}", publisher, year(entry)), output)
+        }
+        (Some(publisher), CitationStyle::AuthorYear) => push_sentence(publisher, output),
+        (None, CitationStyle::Numeric) => push_sentence(year(entry), output),
+        (None, CitationStyle::AuthorYear) => (),
+    }
+
+    let link = match (&entry.doi, &entry.url) {
+        (Some(doi), _) => Some(format!("https://doi.org/{}", doi)),
+        (None, Some(url)) => Some(url.clone()),
+        (None, None) => None,
+    };
+    if let Some(link) = link {
+        *output += &format!(
+            r#"<a href="{}">{}</a>"#,
+            html_escape::encode_double_quoted_attribute(&link),
+            html_escape::encode_text(&link)
+        );
+    }
+
+    while output.ends_with(' ') {
+        output.pop();
+    }
+}
+
+/// Pushes `text` followed by a period unless it ends with punctuation already.
+fn push_sentence(text: &str, output: &mut String) {
+    *output += &html_escape::encode_text(text);
+    if !text.ends_with(['.', '?', '!']) {
+        *output += ".";
+    }
+    *output += " ";
+}
+
+fn reference_id(entry: &Entry) -> String {
+    format!(
+        "ref-{}",
+        html_escape::encode_double_quoted_attribute(&entry.key)
+    )
+}
+
+fn year(entry: &Entry) -> &str {
+    entry.year.as_deref().unwrap_or("n.d.")
+}
+
+/// `Doe`, `Doe and Roe` or `Doe et al.`, falling back to the title.
+fn short_authors(entry: &Entry) -> String {
+    match entry.authors.as_slice() {
+        [] => entry.title.clone().unwrap_or_else(|| entry.key.clone()),
+        [author] => author.family.clone(),
+        [first, second] => format!("{} and {}", first.family, second.family),
+        [first, ..] => format!("{} et al.", first.family),
+    }
+}
+
+/// `Doe, Jane, Richard Roe and ACME`, inverting only the first name for sorting.
+fn full_authors(entry: &Entry) -> Option<String> {
+    let mut authors = String::new();
+    for (idx, author) in entry.authors.iter().enumerate() {
+        if idx > 0 {
+            authors += if idx + 1 == entry.authors.len() {
+                " and "
+            } else {
+                ", "
+            };
+        }
+        match (&author.given, idx) {
+            (Some(given), 0) => authors += &format!("{}, {}", author.family, given),
+            (Some(given), _) => authors += &format!("{} {}", given, author.family),
+            (None, _) => authors += &author.family,
+        }
+    }
+    Some(authors).filter(|authors| !authors.is_empty())
+}
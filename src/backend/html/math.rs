@@ -0,0 +1,812 @@
+//! Translation of the supported TeX subset into MathML.
+
+/// Commands whose argument is taken verbatim instead of being parsed as math.
+const VERBATIM_COMMANDS: &[&str] = &[
+    "text",
+    "textrm",
+    "textit",
+    "textbf",
+    "mbox",
+    "operatorname",
+    "begin",
+    "end",
+];
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Token<'a> {
+    Command(&'a str),
+    Text(&'a str),
+    Letter(char),
+    Number(&'a str),
+    Symbol(char),
+    GroupOpen,
+    GroupClose,
+    Superscript,
+    Subscript,
+    ColumnSeparator,
+    RowSeparator,
+}
+
+pub(super) fn generate_math(source: &str, display: bool, output: &mut String) {
+    let mut parser = Parser {
+        tokens: tokenize(source),
+        pos: 0,
+        display,
+        variant: None,
+    };
+
+    if display {
+        *output += r#"<math display="block">"#;
+    } else {
+        *output += "<math>";
+    }
+    while parser.peek().is_some() {
+        *output += &parser.parse_row(|_| false).concat();
+        // Only an unbalanced closing brace stops a row at the top level
+        if parser.peek() == Some(Token::GroupClose) {
+            parser.pos += 1;
+            *output += &error("}");
+        }
+    }
+    *output += "</math>";
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\\' => {
+                let name_start = start + 1;
+                let mut name_end = name_start;
+                while let Some(&(idx, c)) = chars.peek() {
+                    if !c.is_ascii_alphabetic() {
+                        break;
+                    }
+                    name_end = idx + c.len_utf8();
+                    chars.next();
+                }
+
+                if name_end == name_start {
+                    // Control symbols like `\,` or `\{` consist of a single character
+                    match chars.next() {
+                        Some((_, '\\')) => tokens.push(Token::RowSeparator),
+                        Some((idx, c)) => {
+                            tokens.push(Token::Command(&source[idx..idx + c.len_utf8()]))
+                        }
+                        None => tokens.push(Token::Symbol('\\')),
+                    }
+                    continue;
+                }
+
+                let name = &source[name_start..name_end];
+                tokens.push(Token::Command(name));
+
+                if VERBATIM_COMMANDS.contains(&name) {
+                    while let Some(&(_, c)) = chars.peek() {
+                        if !c.is_whitespace() {
+                            break;
+                        }
+                        chars.next();
+                    }
+                    if let Some(&(open, '{')) = chars.peek() {
+                        chars.next();
+                        let mut depth = 0;
+                        let mut end = source.len();
+                        for (idx, c) in chars.by_ref() {
+                            match c {
+                                '{' => depth += 1,
+                                '}' if depth == 0 => {
+                                    end = idx;
+                                    break;
+                                }
+                                '}' => depth -= 1,
+                                _ => (),
+                            }
+                        }
+                        tokens.push(Token::Text(&source[open + 1..end]));
+                    }
+                }
+            }
+            '{' => tokens.push(Token::GroupOpen),
+            '}' => tokens.push(Token::GroupClose),
+            '^' => tokens.push(Token::Superscript),
+            '_' => tokens.push(Token::Subscript),
+            '&' => tokens.push(Token::ColumnSeparator),
+            c if c.is_whitespace() => (),
+            c if c.is_ascii_digit() => {
+                let mut end = start + 1;
+                while let Some(&(idx, c)) = chars.peek() {
+                    let decimal_point =
+                        c == '.' && source[idx + 1..].starts_with(|c: char| c.is_ascii_digit());
+                    if !c.is_ascii_digit() && !decimal_point {
+                        break;
+                    }
+                    end = idx + 1;
+                    chars.next();
+                }
+                tokens.push(Token::Number(&source[start..end]));
+            }
+            c if c.is_alphabetic() => tokens.push(Token::Letter(c)),
+            c => tokens.push(Token::Symbol(c)),
+        }
+    }
+
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    display: bool,
+    /// Value of the `mathvariant` attribute set by font commands like `\mathbf`.
+    variant: Option<&'static str>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// Parses nodes until the end of the current group or a token matching `stop`.
+    fn parse_row(&mut self, stop: fn(Token<'a>) -> bool) -> Vec<String> {
+        let mut nodes = Vec::new();
+        while let Some(token) = self.peek() {
+            if token == Token::GroupClose || stop(token) {
+                break;
+            }
+            if let Some(node) = self.parse_scripted() {
+                nodes.push(node);
+            }
+        }
+        nodes
+    }
+
+    fn parse_group(&mut self) -> String {
+        let nodes = self.parse_row(|_| false);
+        if self.peek() == Some(Token::GroupClose) {
+            self.pos += 1;
+        }
+        mrow(nodes)
+    }
+
+    fn parse_argument(&mut self) -> String {
+        match self.peek() {
+            Some(Token::GroupOpen) => {
+                self.pos += 1;
+                self.parse_group()
+            }
+            None | Some(Token::GroupClose) => mrow(Vec::new()),
+            Some(_) => self
+                .parse_atom()
+                .map(|(node, _)| node)
+                .unwrap_or_else(|| mrow(Vec::new())),
+        }
+    }
+
+    fn parse_scripted(&mut self) -> Option<String> {
+        let (base, limits) = match self.peek()? {
+            Token::Superscript | Token::Subscript => (mrow(Vec::new()), false),
+            _ => self.parse_atom()?,
+        };
+
+        let mut subscript = None;
+        let mut superscript = None;
+        loop {
+            match self.peek() {
+                Some(Token::Subscript) if subscript.is_none() => {
+                    self.pos += 1;
+                    subscript = Some(self.parse_argument());
+                }
+                Some(Token::Superscript) if superscript.is_none() => {
+                    self.pos += 1;
+                    superscript = Some(self.parse_argument());
+                }
+                _ => break,
+            }
+        }
+
+        let (tag, scripts) = match (subscript, superscript) {
+            (Some(sub), Some(sup)) => (if limits { "munderover" } else { "msubsup" }, sub + &sup),
+            (Some(sub), None) => (if limits { "munder" } else { "msub" }, sub),
+            (None, Some(sup)) => (if limits { "mover" } else { "msup" }, sup),
+            (None, None) => return Some(base),
+        };
+        Some(format!("<{tag}>{}{}</{tag}>", base, scripts, tag = tag))
+    }
+
+    /// Parses a single node without scripts. The flag tells whether scripts
+    /// attached to it are placed above and below instead of to the side.
+    fn parse_atom(&mut self) -> Option<(String, bool)> {
+        let node = match self.next()? {
+            Token::Letter(c) => self.token("mi", &c.to_string(), None),
+            Token::Number(number) => self.token("mn", number, None),
+            Token::Symbol(c) => operator(symbol_operator(c)),
+            Token::GroupOpen => self.parse_group(),
+            Token::GroupClose => error("}"),
+            Token::Text(text) => format!("<mtext>{}</mtext>", escape(text)),
+            Token::Command(name) => return self.parse_command(name),
+            Token::Superscript
+            | Token::Subscript
+            | Token::ColumnSeparator
+            | Token::RowSeparator => return None,
+        };
+        Some((node, false))
+    }
+
+    fn parse_command(&mut self, name: &'a str) -> Option<(String, bool)> {
+        if let Some(c) = greek_letter(name) {
+            // Upright capitals are the convention in TeX
+            let default_variant = if c.is_uppercase() {
+                Some("normal")
+            } else {
+                None
+            };
+            return Some((self.token("mi", &c.to_string(), default_variant), false));
+        }
+        if let Some(c) = identifier_symbol(name) {
+            return Some((self.token("mi", &c.to_string(), None), false));
+        }
+        if let Some((c, movable_limits)) = large_operator(name) {
+            return Some((operator(c), movable_limits && self.display));
+        }
+        if let Some(c) = operator_symbol(name) {
+            return Some((operator(c), false));
+        }
+        if let Some((text, movable_limits)) = function_name(name) {
+            return Some((format!("<mi>{}</mi>", text), movable_limits && self.display));
+        }
+        if let Some(variant) = font_variant(name) {
+            let previous = self.variant.replace(variant);
+            let argument = self.parse_argument();
+            self.variant = previous;
+            return Some((argument, false));
+        }
+        if let Some((accent, over, stretchy)) = accent(name) {
+            let base = self.parse_argument();
+            let stretchy = if stretchy { r#" stretchy="true""# } else { "" };
+            let node = if over {
+                format!(
+                    r#"<mover accent="true">{}<mo{}>{}</mo></mover>"#,
+                    base, stretchy, accent
+                )
+            } else {
+                format!(
+                    r#"<munder accentunder="true">{}<mo{}>{}</mo></munder>"#,
+                    base, stretchy, accent
+                )
+            };
+            // Braces take their annotation above or below like limits
+            return Some((node, name.ends_with("brace")));
+        }
+        if let Some(width) = space_width(name) {
+            return Some((format!(r#"<mspace width="{}"></mspace>"#, width), false));
+        }
+
+        let node = match name {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.parse_argument();
+                let denominator = self.parse_argument();
+                format!("<mfrac>{}{}</mfrac>", numerator, denominator)
+            }
+            "binom" => {
+                let top = self.parse_argument();
+                let bottom = self.parse_argument();
+                format!(
+                    r#"<mrow><mo>(</mo><mfrac linethickness="0">{}{}</mfrac><mo>)</mo></mrow>"#,
+                    top, bottom
+                )
+            }
+            "sqrt" => {
+                if self.peek() == Some(Token::Symbol('[')) {
+                    self.pos += 1;
+                    let index = mrow(self.parse_row(|token| token == Token::Symbol(']')));
+                    if self.peek() == Some(Token::Symbol(']')) {
+                        self.pos += 1;
+                    }
+                    let radicand = self.parse_argument();
+                    format!("<mroot>{}{}</mroot>", radicand, index)
+                } else {
+                    format!("<msqrt>{}</msqrt>", self.parse_argument())
+                }
+            }
+            "left" => self.parse_fenced(),
+            "text" | "textrm" | "textit" | "textbf" | "mbox" => {
+                let text = self.verbatim_argument();
+                match name {
+                    "textit" => format!(r#"<mtext mathvariant="italic">{}</mtext>"#, escape(text)),
+                    "textbf" => format!(r#"<mtext mathvariant="bold">{}</mtext>"#, escape(text)),
+                    _ => format!("<mtext>{}</mtext>", escape(text)),
+                }
+            }
+            "operatorname" => format!("<mi>{}</mi>", escape(self.verbatim_argument().trim())),
+            "begin" => self.parse_environment(),
+            "!" => return None,
+            _ => error(&format!("\\{}", name)),
+        };
+        Some((node, false))
+    }
+
+    fn verbatim_argument(&mut self) -> &'a str {
+        match self.peek() {
+            Some(Token::Text(text)) => {
+                self.pos += 1;
+                text
+            }
+            _ => "",
+        }
+    }
+
+    /// Parses the contents of `\left ... \right` after the `\left`.
+    fn parse_fenced(&mut self) -> String {
+        let open = self.parse_delimiter();
+        let inner = self.parse_row(|token| token == Token::Command("right"));
+        let close = if self.peek() == Some(Token::Command("right")) {
+            self.pos += 1;
+            self.parse_delimiter()
+        } else {
+            String::new()
+        };
+        format!("<mrow>{}{}{}</mrow>", open, inner.concat(), close)
+    }
+
+    fn parse_delimiter(&mut self) -> String {
+        let delimiter = match self.peek() {
+            // `.` stands for an invisible delimiter
+            Some(Token::Symbol('.')) => None,
+            Some(Token::Symbol(c)) => Some(symbol_operator(c)),
+            Some(Token::Command(name)) => match operator_symbol(name) {
+                Some(c) => Some(c),
+                None => {
+                    self.pos += 1;
+                    return error(&format!("\\{}", name));
+                }
+            },
+            _ => return String::new(),
+        };
+        self.pos += 1;
+        delimiter.map(fence).unwrap_or_default()
+    }
+
+    /// Parses the body of `\begin{name} ... \end{name}` after the `\begin`.
+    fn parse_environment(&mut self) -> String {
+        let name = self.verbatim_argument().trim();
+        let (open, close, column_align) = match name {
+            "matrix" => (None, None, None),
+            "pmatrix" => (Some('('), Some(')'), None),
+            "bmatrix" => (Some('['), Some(']'), None),
+            "Bmatrix" => (Some('{'), Some('}'), None),
+            "vmatrix" => (Some('|'), Some('|'), None),
+            "Vmatrix" => (Some('‖'), Some('‖'), None),
+            "cases" => (Some('{'), None, Some("left left")),
+            "aligned" | "align" | "align*" => (None, None, Some("right left")),
+            _ => return error(&format!("\\begin{{{}}}", name)),
+        };
+
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        loop {
+            let cell = self.parse_row(|token| {
+                matches!(
+                    token,
+                    Token::ColumnSeparator | Token::RowSeparator | Token::Command("end")
+                )
+            });
+            row.push(cell.concat());
+            match self.peek() {
+                Some(Token::ColumnSeparator) => self.pos += 1,
+                Some(Token::RowSeparator) => {
+                    self.pos += 1;
+                    rows.push(std::mem::take(&mut row));
+                }
+                Some(Token::Command("end")) => {
+                    self.pos += 1;
+                    self.verbatim_argument();
+                    break;
+                }
+                _ => break,
+            }
+        }
+        // A trailing `\\` does not start another row
+        if row.len() > 1 || row.first().is_some_and(|cell| !cell.is_empty()) {
+            rows.push(row);
+        }
+
+        let mut output = String::new();
+        if open.is_some() || close.is_some() {
+            output += "<mrow>";
+        }
+        if let Some(open) = open {
+            output += &fence(open);
+        }
+        match column_align {
+            Some(column_align) => output += &format!(r#"<mtable columnalign="{}">"#, column_align),
+            None => output += "<mtable>",
+        }
+        for row in rows {
+            output += "<mtr>";
+            for cell in row {
+                output += &format!("<mtd>{}</mtd>", cell);
+            }
+            output += "</mtr>";
+        }
+        output += "</mtable>";
+        if let Some(close) = close {
+            output += &fence(close);
+        }
+        if open.is_some() || close.is_some() {
+            output += "</mrow>";
+        }
+        output
+    }
+
+    fn token(&self, tag: &str, content: &str, default_variant: Option<&str>) -> String {
+        match self.variant.or(default_variant) {
+            Some(variant) => format!(
+                r#"<{tag} mathvariant="{}">{}</{tag}>"#,
+                variant,
+                escape(content),
+                tag = tag
+            ),
+            None => format!("<{tag}>{}</{tag}>", escape(content), tag = tag),
+        }
+    }
+}
+
+fn mrow(nodes: Vec<String>) -> String {
+    if nodes.len() == 1 {
+        nodes.into_iter().next().unwrap()
+    } else {
+        format!("<mrow>{}</mrow>", nodes.concat())
+    }
+}
+
+fn operator(c: char) -> String {
+    format!("<mo>{}</mo>", escape(&c.to_string()))
+}
+
+fn fence(c: char) -> String {
+    format!(
+        r#"<mo fence="true" stretchy="true">{}</mo>"#,
+        escape(&c.to_string())
+    )
+}
+
+fn error(source: &str) -> String {
+    format!("<merror><mtext>{}</mtext></merror>", escape(source))
+}
+
+fn escape(text: &str) -> std::borrow::Cow<'_, str> {
+    html_escape::encode_text(text)
+}
+
+fn symbol_operator(c: char) -> char {
+    match c {
+        '-' => '−',
+        '*' => '∗',
+        '\'' => '′',
+        c => c,
+    }
+}
+
+fn greek_letter(name: &str) -> Option<char> {
+    Some(match name {
+        "alpha" => 'α',
+        "beta" => 'β',
+        "gamma" => 'γ',
+        "delta" => 'δ',
+        "epsilon" => 'ϵ',
+        "varepsilon" => 'ε',
+        "zeta" => 'ζ',
+        "eta" => 'η',
+        "theta" => 'θ',
+        "vartheta" => 'ϑ',
+        "iota" => 'ι',
+        "kappa" => 'κ',
+        "lambda" => 'λ',
+        "mu" => 'μ',
+        "nu" => 'ν',
+        "xi" => 'ξ',
+        "omicron" => 'ο',
+        "pi" => 'π',
+        "varpi" => 'ϖ',
+        "rho" => 'ρ',
+        "varrho" => 'ϱ',
+        "sigma" => 'σ',
+        "varsigma" => 'ς',
+        "tau" => 'τ',
+        "upsilon" => 'υ',
+        "phi" => 'ϕ',
+        "varphi" => 'φ',
+        "chi" => 'χ',
+        "psi" => 'ψ',
+        "omega" => 'ω',
+        "Gamma" => 'Γ',
+        "Delta" => 'Δ',
+        "Theta" => 'Θ',
+        "Lambda" => 'Λ',
+        "Xi" => 'Ξ',
+        "Pi" => 'Π',
+        "Sigma" => 'Σ',
+        "Upsilon" => 'Υ',
+        "Phi" => 'Φ',
+        "Psi" => 'Ψ',
+        "Omega" => 'Ω',
+        _ => return None,
+    })
+}
+
+fn identifier_symbol(name: &str) -> Option<char> {
+    Some(match name {
+        "infty" => '∞',
+        "partial" => '∂',
+        "nabla" => '∇',
+        "emptyset" | "varnothing" => '∅',
+        "hbar" => 'ℏ',
+        "ell" => 'ℓ',
+        "aleph" => 'ℵ',
+        "Re" => 'ℜ',
+        "Im" => 'ℑ',
+        "wp" => '℘',
+        _ => return None,
+    })
+}
+
+fn operator_symbol(name: &str) -> Option<char> {
+    Some(match name {
+        "pm" => '±',
+        "mp" => '∓',
+        "times" => '×',
+        "div" => '÷',
+        "cdot" => '⋅',
+        "ast" => '∗',
+        "star" => '⋆',
+        "circ" => '∘',
+        "bullet" => '∙',
+        "oplus" => '⊕',
+        "ominus" => '⊖',
+        "otimes" => '⊗',
+        "cup" => '∪',
+        "cap" => '∩',
+        "setminus" => '∖',
+        "wedge" | "land" => '∧',
+        "vee" | "lor" => '∨',
+        "neg" | "lnot" => '¬',
+        "leq" | "le" => '≤',
+        "geq" | "ge" => '≥',
+        "neq" | "ne" => '≠',
+        "approx" => '≈',
+        "equiv" => '≡',
+        "sim" => '∼',
+        "simeq" => '≃',
+        "cong" => '≅',
+        "propto" => '∝',
+        "ll" => '≪',
+        "gg" => '≫',
+        "subset" => '⊂',
+        "supset" => '⊃',
+        "subseteq" => '⊆',
+        "supseteq" => '⊇',
+        "in" => '∈',
+        "notin" => '∉',
+        "ni" => '∋',
+        "mid" => '∣',
+        "parallel" => '∥',
+        "perp" => '⊥',
+        "to" | "rightarrow" => '→',
+        "leftarrow" | "gets" => '←',
+        "leftrightarrow" => '↔',
+        "Rightarrow" => '⇒',
+        "Leftarrow" => '⇐',
+        "Leftrightarrow" => '⇔',
+        "implies" => '⟹',
+        "iff" => '⟺',
+        "mapsto" => '↦',
+        "uparrow" => '↑',
+        "downarrow" => '↓',
+        "forall" => '∀',
+        "exists" => '∃',
+        "nexists" => '∄',
+        "ldots" | "dots" => '…',
+        "cdots" => '⋯',
+        "vdots" => '⋮',
+        "ddots" => '⋱',
+        "langle" => '⟨',
+        "rangle" => '⟩',
+        "lfloor" => '⌊',
+        "rfloor" => '⌋',
+        "lceil" => '⌈',
+        "rceil" => '⌉',
+        "vert" => '|',
+        "Vert" | "|" => '‖',
+        "lbrace" | "{" => '{',
+        "rbrace" | "}" => '}',
+        "prime" => '′',
+        "angle" => '∠',
+        "triangle" => '△',
+        "colon" => ':',
+        "%" => '%',
+        "$" => '$',
+        "#" => '#',
+        "&" => '&',
+        "_" => '_',
+        _ => return None,
+    })
+}
+
+/// Large operators and whether they take limits above and below in display math.
+fn large_operator(name: &str) -> Option<(char, bool)> {
+    Some(match name {
+        "sum" => ('∑', true),
+        "prod" => ('∏', true),
+        "coprod" => ('∐', true),
+        "bigcup" => ('⋃', true),
+        "bigcap" => ('⋂', true),
+        "bigvee" => ('⋁', true),
+        "bigwedge" => ('⋀', true),
+        "bigoplus" => ('⨁', true),
+        "bigotimes" => ('⨂', true),
+        "int" => ('∫', false),
+        "iint" => ('∬', false),
+        "iiint" => ('∭', false),
+        "oint" => ('∮', false),
+        _ => return None,
+    })
+}
+
+/// Function names and whether they take limits above and below in display math.
+fn function_name(name: &str) -> Option<(&'static str, bool)> {
+    Some(match name {
+        "sin" => ("sin", false),
+        "cos" => ("cos", false),
+        "tan" => ("tan", false),
+        "cot" => ("cot", false),
+        "sec" => ("sec", false),
+        "csc" => ("csc", false),
+        "arcsin" => ("arcsin", false),
+        "arccos" => ("arccos", false),
+        "arctan" => ("arctan", false),
+        "sinh" => ("sinh", false),
+        "cosh" => ("cosh", false),
+        "tanh" => ("tanh", false),
+        "coth" => ("coth", false),
+        "log" => ("log", false),
+        "ln" => ("ln", false),
+        "lg" => ("lg", false),
+        "exp" => ("exp", false),
+        "dim" => ("dim", false),
+        "deg" => ("deg", false),
+        "arg" => ("arg", false),
+        "ker" => ("ker", false),
+        "hom" => ("hom", false),
+        "det" => ("det", true),
+        "gcd" => ("gcd", true),
+        "max" => ("max", true),
+        "min" => ("min", true),
+        "sup" => ("sup", true),
+        "inf" => ("inf", true),
+        "lim" => ("lim", true),
+        "liminf" => ("lim inf", true),
+        "limsup" => ("lim sup", true),
+        "Pr" => ("Pr", true),
+        _ => return None,
+    })
+}
+
+fn font_variant(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "mathrm" => "normal",
+        "mathit" => "italic",
+        "mathbf" => "bold",
+        "boldsymbol" => "bold-italic",
+        "mathbb" => "double-struck",
+        "mathcal" => "script",
+        "mathfrak" => "fraktur",
+        "mathsf" => "sans-serif",
+        "mathtt" => "monospace",
+        _ => return None,
+    })
+}
+
+/// Accent character, whether it is placed above the base and whether it stretches.
+fn accent(name: &str) -> Option<(char, bool, bool)> {
+    Some(match name {
+        "hat" => ('^', true, false),
+        "widehat" => ('^', true, true),
+        "check" => ('ˇ', true, false),
+        "tilde" => ('~', true, false),
+        "widetilde" => ('~', true, true),
+        "bar" => ('¯', true, false),
+        "overline" => ('¯', true, true),
+        "vec" => ('→', true, false),
+        "overrightarrow" => ('→', true, true),
+        "overleftarrow" => ('←', true, true),
+        "dot" => ('˙', true, false),
+        "ddot" => ('¨', true, false),
+        "acute" => ('´', true, false),
+        "grave" => ('`', true, false),
+        "breve" => ('˘', true, false),
+        "overbrace" => ('⏞', true, true),
+        "underline" => ('_', false, true),
+        "underbrace" => ('⏟', false, true),
+        _ => return None,
+    })
+}
+
+fn space_width(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "," => "0.1667em",
+        ":" | ">" => "0.2222em",
+        ";" => "0.2778em",
+        " " => "0.25em",
+        "quad" => "1em",
+        "qquad" => "2em",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn math(source: &str, display: bool) -> String {
+        let mut output = String::new();
+        generate_math(source, display, &mut output);
+        output
+    }
+
+    #[test]
+    fn scripts_and_fractions() {
+        assert_eq!(
+            math(r"x^2 + \frac{a}{b_1}", false),
+            "<math><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo>\
+             <mfrac><mi>a</mi><msub><mi>b</mi><mn>1</mn></msub></mfrac></math>"
+        );
+        assert_eq!(
+            math(r"\sum_{i=1}^n i", true),
+            r#"<math display="block"><munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover><mi>i</mi></math>"#
+        );
+        assert_eq!(
+            math(r"\sum_{i=1}^n i", false),
+            "<math><msubsup><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></msubsup><mi>i</mi></math>"
+        );
+    }
+
+    #[test]
+    fn greek_roots_and_text() {
+        assert_eq!(
+            math(r"\alpha \Omega \sqrt{2} \sqrt[3]{x}", false),
+            r#"<math><mi>α</mi><mi mathvariant="normal">Ω</mi><msqrt><mn>2</mn></msqrt><mroot><mi>x</mi><mn>3</mn></mroot></math>"#
+        );
+        assert_eq!(
+            math(r"\text{if } x < 1", false),
+            "<math><mtext>if </mtext><mi>x</mi><mo>&lt;</mo><mn>1</mn></math>"
+        );
+    }
+
+    #[test]
+    fn matrices() {
+        assert_eq!(
+            math(r"\begin{pmatrix} a & b \\ c & d \end{pmatrix}", true),
+            r#"<math display="block"><mrow><mo fence="true" stretchy="true">(</mo><mtable><mtr><mtd><mi>a</mi></mtd><mtd><mi>b</mi></mtd></mtr><mtr><mtd><mi>c</mi></mtd><mtd><mi>d</mi></mtd></mtr></mtable><mo fence="true" stretchy="true">)</mo></mrow></math>"#
+        );
+    }
+
+    #[test]
+    fn unknown_commands() {
+        assert_eq!(
+            math(r"\foo x}", false),
+            "<math><merror><mtext>\\foo</mtext></merror><mi>x</mi>\
+             <merror><mtext>}</mtext></merror></math>"
+        );
+    }
+}
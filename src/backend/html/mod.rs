@@ -1,4 +1,7 @@
-use crate::ast::hir;
+mod citation;
+mod math;
+
+use crate::ast::{hir, Metadata};
 
 #[derive(Debug)]
 pub struct HtmlBackend;
@@ -7,65 +10,318 @@ impl super::Backend for HtmlBackend {
     type Output = String;
     type Error = std::convert::Infallible;
 
+    const NAME: &'static str = "html";
+
     fn compile_hir(document: &hir::Document<'_>) -> Result<Self::Output, Self::Error> {
-        let mut output = r#"<div class="writer4-doc">"#.to_string();
-
-        let mut statements = document.statements.iter();
-        while let Some(statement) = statements.next() {
-            match statement {
-                hir::Statement::Header(hir::HeaderStatement { header_type, text }) => {
-                    let htag = match header_type {
-                        hir::HeaderType::H1 => "h1",
-                        hir::HeaderType::H2 => "h2",
-                        hir::HeaderType::H3 => "h3",
-                        hir::HeaderType::H4 => "h4",
-                        hir::HeaderType::H5 => "h5",
-                        hir::HeaderType::H6 => "h6",
-                    };
-
-                    let mut text_output = String::new();
-                    for segment in &text.segments {
-                        generate_text(segment, &mut text_output);
-                    }
+        let mut output = match document.metadata.lang() {
+            Some(lang) => format!(
+                r#"<div class="writer4-doc" lang="{}">"#,
+                html_escape::encode_double_quoted_attribute(lang)
+            ),
+            None => r#"<div class="writer4-doc">"#.to_string(),
+        };
+
+        generate_statements(&document.statements, &mut output);
+        generate_footnotes(&document.footnotes, &mut output);
+
+        output += "</div>";
 
-                    output += &format!("<{htag}>{text}</{htag}>", htag = htag, text = text_output);
+        Ok(output)
+    }
+}
+
+impl HtmlBackend {
+    /// Wraps compiled `body` into a complete HTML page whose head is filled
+    /// from `metadata`.
+    pub fn page(metadata: &Metadata<'_>, body: &str) -> String {
+        let mut output = "<!DOCTYPE html>".to_string();
+        match metadata.lang() {
+            Some(lang) => {
+                output += &format!(
+                    r#"<html lang="{}">"#,
+                    html_escape::encode_double_quoted_attribute(lang)
+                )
+            }
+            None => output += "<html>",
+        }
+        output += r#"<head><meta charset="utf-8">"#;
+        if let Some(title) = metadata.title() {
+            output += &format!("<title>{}</title>", html_escape::encode_text(title));
+        }
+        if let Some(author) = metadata.author() {
+            output += &format!(
+                r#"<meta name="author" content="{}">"#,
+                html_escape::encode_double_quoted_attribute(author)
+            );
+        }
+        let tags = metadata.tags();
+        if !tags.is_empty() {
+            output += &format!(
+                r#"<meta name="keywords" content="{}">"#,
+                html_escape::encode_double_quoted_attribute(&tags.join(", "))
+            );
+        }
+        output += "</head><body>";
+        output += body;
+        output += "</body></html>";
+        output
+    }
+}
+
+fn generate_statements(statements: &[hir::Statement<'_>], output: &mut String) {
+    for statement in statements {
+        match statement {
+            hir::Statement::Header(hir::HeaderStatement {
+                header_type,
+                id,
+                attributes,
+                text,
+            }) => {
+                let htag = match header_type {
+                    hir::HeaderType::H1 => "h1",
+                    hir::HeaderType::H2 => "h2",
+                    hir::HeaderType::H3 => "h3",
+                    hir::HeaderType::H4 => "h4",
+                    hir::HeaderType::H5 => "h5",
+                    hir::HeaderType::H6 => "h6",
+                };
+
+                let mut text_output = String::new();
+                for segment in &text.segments {
+                    generate_text(segment, &mut text_output);
+                }
+
+                *output += &format!(
+                    r#"<{htag} id="{id}"{attributes}>{text}</{htag}>"#,
+                    htag = htag,
+                    id = html_escape::encode_double_quoted_attribute(id),
+                    attributes = generate_attributes(attributes),
+                    text = text_output
+                );
+            }
+            hir::Statement::Paragraph(hir::ParagraphStatement { attributes, text }) => {
+                let mut text_output = String::new();
+                for segment in &text.segments {
+                    generate_text(segment, &mut text_output);
                 }
-                hir::Statement::Paragraph(hir::ParagraphStatement { text }) => {
-                    let mut text_output = String::new();
-                    for segment in &text.segments {
-                        generate_text(segment, &mut text_output);
-                    }
 
-                    output += &format!("<p>{}</p>", text_output);
+                *output += &format!("<p{}>{}</p>", generate_attributes(attributes), text_output);
+            }
+            hir::Statement::List(hir::ListStatement { attributes, list }) => {
+                generate_list(list, &generate_attributes(attributes), output);
+            }
+            hir::Statement::HorizontalRule(_) => *output += "<hr>",
+            hir::Statement::CodeBlock(hir::CodeBlockStatement { info, content }) => {
+                // Only the first word of the info string names the language
+                match info.and_then(|info| info.split_whitespace().next()) {
+                    Some(language) => {
+                        *output += r#"<pre><code class="language-"#;
+                        *output += &html_escape::encode_double_quoted_attribute(language);
+                        *output += r#"">"#;
+                    }
+                    None => *output += "<pre><code>",
+                }
+                *output += &html_escape::encode_text(content);
+                *output += "</code></pre>";
+            }
+            hir::Statement::Raw(hir::RawStatement { target, content }) => {
+                if *target == <HtmlBackend as super::Backend>::NAME {
+                    *output += content;
+                }
+            }
+            hir::Statement::MathBlock(hir::MathBlockStatement { content }) => {
+                math::generate_math(content, true, output);
+            }
+            hir::Statement::Figure(hir::FigureStatement { image, caption }) => {
+                *output += "<figure>";
+                generate_image(image, output);
+                if !caption.segments.is_empty() {
+                    *output += "<figcaption>";
+                    for segment in &caption.segments {
+                        generate_text(segment, output);
+                    }
+                    *output += "</figcaption>";
                 }
-                hir::Statement::List(hir::ListStatement { list }) => {
-                    generate_list(list, &mut output);
+                *output += "</figure>";
+            }
+            hir::Statement::Table(table) => generate_table(table, output),
+            hir::Statement::Bibliography(hir::BibliographyStatement { style, entries }) => {
+                citation::generate_bibliography(*style, entries, output)
+            }
+            hir::Statement::TableOfContents(hir::TableOfContentsStatement { list, .. }) => {
+                *output += r#"<nav class="writer4-toc">"#;
+                if !list.items.is_empty() {
+                    generate_list(list, "", output);
                 }
-                hir::Statement::HorizontalRule(_) => output += "<hr>",
+                *output += "</nav>";
+            }
+            hir::Statement::DefinitionList(hir::DefinitionListStatement { attributes, items }) => {
+                *output += &format!("<dl{}>", generate_attributes(attributes));
+                for item in items {
+                    if !item.term.segments.is_empty() {
+                        *output += "<dt>";
+                        for segment in &item.term.segments {
+                            generate_text(segment, output);
+                        }
+                        *output += "</dt>";
+                    }
+                    for definition in &item.definitions {
+                        *output += "<dd>";
+                        for segment in &definition.segments {
+                            generate_text(segment, output);
+                        }
+                        *output += "</dd>";
+                    }
+                }
+                *output += "</dl>";
+            }
+            hir::Statement::Admonition(hir::AdmonitionStatement {
+                kind,
+                attributes,
+                title,
+                statements,
+            }) => {
+                let kind = match kind {
+                    hir::AdmonitionKind::Note => "writer4-note",
+                    hir::AdmonitionKind::Tip => "writer4-tip",
+                    hir::AdmonitionKind::Warning => "writer4-warning",
+                    hir::AdmonitionKind::Danger => "writer4-danger",
+                };
+                let mut classes = vec!["writer4-admonition", kind];
+                classes.extend(&attributes.classes);
+                let attributes = hir::Attributes {
+                    classes,
+                    ..attributes.clone()
+                };
+                *output += &format!("<aside{}>", generate_attributes(&attributes));
+                if let Some(title) = title {
+                    *output += r#"<p class="writer4-admonition-title">"#;
+                    for segment in &title.segments {
+                        generate_text(segment, output);
+                    }
+                    *output += "</p>";
+                }
+                generate_statements(statements, output);
+                *output += "</aside>";
+            }
+            hir::Statement::BlockQuote(hir::BlockQuoteStatement { statements }) => {
+                *output += "<blockquote>";
+                generate_statements(statements, output);
+                *output += "</blockquote>";
             }
         }
+    }
+}
 
-        output += "</div>";
+fn generate_table(table: &hir::TableStatement<'_>, output: &mut String) {
+    *output += "<table>";
+    if let Some(header) = &table.header {
+        *output += "<thead>";
+        generate_table_row(header, "th", &table.alignments, output);
+        *output += "</thead>";
+    }
+    if !table.rows.is_empty() {
+        *output += "<tbody>";
+        for row in &table.rows {
+            generate_table_row(row, "td", &table.alignments, output);
+        }
+        *output += "</tbody>";
+    }
+    *output += "</table>";
+}
 
-        Ok(output)
+fn generate_table_row(
+    row: &hir::TableRow<'_>,
+    tag: &str,
+    alignments: &[Option<hir::Alignment>],
+    output: &mut String,
+) {
+    *output += "<tr>";
+    for (idx, cell) in row.cells.iter().enumerate() {
+        match alignments.get(idx).copied().flatten() {
+            Some(alignment) => {
+                let alignment = match alignment {
+                    hir::Alignment::Left => "left",
+                    hir::Alignment::Center => "center",
+                    hir::Alignment::Right => "right",
+                };
+                *output += &format!(r#"<{} style="text-align: {}">"#, tag, alignment);
+            }
+            None => *output += &format!("<{}>", tag),
+        }
+        for segment in &cell.segments {
+            generate_text(segment, output);
+        }
+        *output += &format!("</{}>", tag);
     }
+    *output += "</tr>";
 }
 
-fn generate_list(list: &hir::List<'_>, output: &mut String) {
-    *output += "<ul>";
+fn generate_footnotes(footnotes: &[hir::Footnote<'_>], output: &mut String) {
+    if footnotes.is_empty() {
+        return;
+    }
+
+    *output += r#"<section class="writer4-footnotes"><ol>"#;
+    for (idx, footnote) in footnotes.iter().enumerate() {
+        let number = idx + 1;
+
+        *output += &format!(r#"<li id="fn-{}">"#, number);
+        for segment in &footnote.text.segments {
+            generate_text(segment, output);
+        }
+        for occurrence in 1..=footnote.references {
+            *output += &format!(
+                r##" <a class="writer4-footnote-backref" href="#{}">↩</a>"##,
+                footnote_reference_id(number, occurrence)
+            );
+        }
+        *output += "</li>";
+    }
+    *output += "</ol></section>";
+}
+
+fn footnote_reference_id(number: usize, occurrence: usize) -> String {
+    match occurrence {
+        1 => format!("fnref-{}", number),
+        occurrence => format!("fnref-{}-{}", number, occurrence),
+    }
+}
+
+/// `attributes` are rendered with [`generate_attributes`].
+fn generate_list(list: &hir::List<'_>, attributes: &str, output: &mut String) {
+    let tag = match list.list_type {
+        hir::ListType::Unordered => {
+            *output += &format!("<ul{}>", attributes);
+            "ul"
+        }
+        hir::ListType::Ordered { start: 1 } => {
+            *output += &format!("<ol{}>", attributes);
+            "ol"
+        }
+        hir::ListType::Ordered { start } => {
+            *output += &format!(r#"<ol start="{}"{}>"#, start, attributes);
+            "ol"
+        }
+    };
     for list_item in &list.items {
         generate_list_item(list_item, output);
     }
-    *output += "</ul>";
+    *output += &format!("</{}>", tag);
 }
 
 fn generate_list_item(list_item: &hir::ListItem<'_>, output: &mut String) {
     *output += "<li>";
+    match list_item.checked {
+        Some(true) => *output += r#"<input type="checkbox" disabled checked> "#,
+        Some(false) => *output += r#"<input type="checkbox" disabled> "#,
+        None => (),
+    }
     for segment in &list_item.text.segments {
         generate_text(segment, output);
     }
-    if let Some(child) = &list_item.child {
-        generate_list(child, output);
+    for child in &list_item.children {
+        generate_list(child, "", output);
     }
     *output += "</li>";
 }
@@ -79,6 +335,10 @@ fn generate_text(segment: &hir::TextSegment<'_>, output: &mut String) {
                 hir::Emphasis::Bold => ("<b>", "</b>"),
                 hir::Emphasis::Italic => ("<i>", "</i>"),
                 hir::Emphasis::Strikethrough => ("<s>", "</s>"),
+                hir::Emphasis::Underline => ("<u>", "</u>"),
+                hir::Emphasis::Mark => ("<mark>", "</mark>"),
+                hir::Emphasis::Superscript => ("<sup>", "</sup>"),
+                hir::Emphasis::Subscript => ("<sub>", "</sub>"),
             };
             *output += tag_opening;
             for segment in inner {
@@ -86,5 +346,99 @@ fn generate_text(segment: &hir::TextSegment<'_>, output: &mut String) {
             }
             *output += tag_closing;
         }
+        hir::TextSegment::Link { label, url, title } => {
+            *output += r#"<a href=""#;
+            *output += &html_escape::encode_double_quoted_attribute(url);
+            *output += "\"";
+            if let Some(title) = title {
+                *output += r#" title=""#;
+                *output += &html_escape::encode_double_quoted_attribute(title);
+                *output += "\"";
+            }
+            *output += ">";
+            for segment in label {
+                generate_text(segment, output);
+            }
+            *output += "</a>";
+        }
+        hir::TextSegment::Image(image) => generate_image(image, output),
+        hir::TextSegment::CrossReference { id, text } => {
+            *output += r##"<a href="#"##;
+            *output += &html_escape::encode_double_quoted_attribute(id);
+            *output += r#"">"#;
+            for segment in text {
+                generate_text(segment, output);
+            }
+            *output += "</a>";
+        }
+        hir::TextSegment::FootnoteReference { number, occurrence } => {
+            *output += &format!(
+                r##"<sup class="writer4-footnote-ref"><a id="{}" href="#fn-{}">{}</a></sup>"##,
+                footnote_reference_id(*number, *occurrence),
+                number,
+                number
+            );
+        }
+        hir::TextSegment::Code(code) => {
+            *output += "<code>";
+            *output += &html_escape::encode_text(code);
+            *output += "</code>";
+        }
+        hir::TextSegment::Math(math) => math::generate_math(math, false, output),
+        hir::TextSegment::Span { inner, attributes } => {
+            *output += &format!("<span{}>", generate_attributes(attributes));
+            for segment in inner {
+                generate_text(segment, output);
+            }
+            *output += "</span>";
+        }
+        hir::TextSegment::Raw { target, content } => {
+            if *target == <HtmlBackend as super::Backend>::NAME {
+                *output += content;
+            }
+        }
+        hir::TextSegment::Citation { style, items } => {
+            citation::generate_citation(*style, items, output)
+        }
+    }
+}
+
+/// Renders `attributes` with a leading space, ready to go into a start tag.
+fn generate_attributes(attributes: &hir::Attributes<'_>) -> String {
+    let mut output = String::new();
+    if let Some(id) = attributes.id {
+        output += &format!(
+            r#" id="{}""#,
+            html_escape::encode_double_quoted_attribute(id)
+        );
+    }
+    if !attributes.classes.is_empty() {
+        output += &format!(
+            r#" class="{}""#,
+            html_escape::encode_double_quoted_attribute(&attributes.classes.join(" "))
+        );
+    }
+    for (key, value) in &attributes.pairs {
+        // Keys are restricted by the grammar to characters valid in HTML
+        output += &format!(
+            r#" {}="{}""#,
+            key,
+            html_escape::encode_double_quoted_attribute(value)
+        );
+    }
+    output
+}
+
+fn generate_image(image: &hir::Image<'_>, output: &mut String) {
+    *output += r#"<img src=""#;
+    *output += &html_escape::encode_double_quoted_attribute(image.url);
+    *output += r#"" alt=""#;
+    *output += &html_escape::encode_double_quoted_attribute(image.alt);
+    *output += "\"";
+    if let Some(title) = image.title {
+        *output += r#" title=""#;
+        *output += &html_escape::encode_double_quoted_attribute(title);
+        *output += "\"";
     }
+    *output += ">";
 }
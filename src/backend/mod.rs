@@ -9,21 +9,49 @@ pub use self::html::HtmlBackend;
 pub use self::pdf::PdfBackend;
 
 use crate::ast::hir;
-use crate::parser;
+use crate::options::Options;
+use crate::parser::{
+    self,
+    include::{FileLoader, Sources},
+};
+use std::path::Path;
 
 pub trait Backend: Sized {
     type Output;
     type Error;
 
+    /// Name that raw blocks use to target the backend.
+    const NAME: &'static str;
+
     fn compile_hir(document: &hir::Document<'_>) -> Result<Self::Output, Self::Error>;
 
-    fn compile(source_code: &str) -> crate::Result<Self::Output, Self>
+    fn compile(source_code: &str, options: &Options) -> crate::Result<Self::Output, Self>
     where
         Self: std::fmt::Debug,
         Self::Error: std::error::Error,
     {
         let doc_lir = parser::lir::parse(source_code)?;
-        let doc_hir = parser::hir::parse(&doc_lir)?;
+        let doc_hir = parser::hir::parse(&doc_lir, options)?;
+
+        match Self::compile_hir(&doc_hir) {
+            Ok(output) => Ok(output),
+            Err(e) => Err(crate::Error::Backend(e)),
+        }
+    }
+
+    /// Compiles the file at `path`, resolving `@include` statements through `loader`.
+    fn compile_file(
+        path: &Path,
+        loader: &dyn FileLoader,
+        options: &Options,
+    ) -> crate::Result<Self::Output, Self>
+    where
+        Self: std::fmt::Debug,
+        Self::Error: std::error::Error,
+    {
+        let sources = Sources::load(path, loader)?;
+        let doc_lir = sources.parse(path)?;
+        let doc_hir = parser::hir::parse(&doc_lir, options)?;
 
         match Self::compile_hir(&doc_hir) {
             Ok(output) => Ok(output),
@@ -7,6 +7,8 @@ impl super::Backend for PdfBackend {
     type Output = std::convert::Infallible;
     type Error = std::convert::Infallible;
 
+    const NAME: &'static str = "pdf";
+
     fn compile_hir(_: &hir::Document<'_>) -> Result<Self::Output, Self::Error> {
         todo!()
     }
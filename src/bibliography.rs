@@ -0,0 +1,84 @@
+use crate::parser::{
+    self,
+    bibliography::{Error, Result},
+    include::FileLoader,
+};
+use std::path::Path;
+
+/// Works that documents can cite with `[@key]`.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Bibliography {
+    entries: Vec<Entry>,
+}
+
+impl Bibliography {
+    /// Parses BibTeX, ignoring `@comment`, `@preamble` and `@string`.
+    pub fn from_bibtex(code: &str) -> Result<Self> {
+        Self::new(parser::bibliography::parse_bibtex(code)?)
+    }
+
+    /// Parses a CSL-JSON array of items.
+    pub fn from_csl_json(code: &str) -> Result<Self> {
+        Self::new(parser::bibliography::parse_csl_json(code)?)
+    }
+
+    /// Loads the file at `path` as BibTeX (`.bib`) or CSL-JSON (`.json`).
+    pub fn load(path: &Path, loader: &dyn FileLoader) -> Result<Self> {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        match extension {
+            Some("bib") => Self::from_bibtex(&loader.load(path)?),
+            Some("json") => Self::from_csl_json(&loader.load(path)?),
+            _ => Err(Error::UnknownFormat(path.to_path_buf())),
+        }
+    }
+
+    fn new(entries: Vec<Entry>) -> Result<Self> {
+        for (idx, entry) in entries.iter().enumerate() {
+            if entries[..idx].iter().any(|other| other.key == entry.key) {
+                return Err(Error::DuplicateKey(entry.key.clone()));
+            }
+        }
+        Ok(Bibliography { entries })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.key == key)
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+/// A citable work. Text fields are plain text without markup.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Entry {
+    pub key: String,
+    pub authors: Vec<Name>,
+    pub title: Option<String>,
+    /// Journal, proceedings or book the work is part of.
+    pub container_title: Option<String>,
+    pub volume: Option<String>,
+    pub pages: Option<String>,
+    pub publisher: Option<String>,
+    pub year: Option<String>,
+    pub doi: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Name {
+    /// Family name, or the full name of an organization.
+    pub family: String,
+    pub given: Option<String>,
+}
+
+/// How citations and the bibliography are formatted.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum CitationStyle {
+    /// `(Doe and Roe 2020)`, with the bibliography sorted by author.
+    #[default]
+    AuthorYear,
+    /// `[1]`, with the bibliography numbered in citation order.
+    Numeric,
+}
@@ -0,0 +1,82 @@
+use crate::ast::hir;
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// A use of a custom directive, with its label and body already parsed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Directive<'a> {
+    pub name: &'a str,
+    pub kind: DirectiveKind,
+    /// `[label]`, or the title of a container.
+    pub label: Vec<hir::TextSegment<'a>>,
+    /// `{#id .class key=value}`.
+    pub arguments: hir::Attributes<'a>,
+    /// Statements inside a container.
+    pub body: Vec<hir::Statement<'a>>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DirectiveKind {
+    /// `:name[label]{arguments}` within text.
+    Inline,
+    /// `::name[label]{arguments}` on its own line.
+    Leaf,
+    /// `:::name{arguments} label` followed by the body and a closing `:::`.
+    Container,
+}
+
+type BlockHandler =
+    dyn for<'a> Fn(Directive<'a>) -> Result<Vec<hir::Statement<'a>>, String> + Send + Sync;
+type InlineHandler =
+    dyn for<'a> Fn(Directive<'a>) -> Result<Vec<hir::TextSegment<'a>>, String> + Send + Sync;
+
+/// Handlers for custom directives, by name.
+///
+/// Handlers produce HIR nodes, which may be [`hir::RawStatement`]s or
+/// [`hir::TextSegment::Raw`] for output specific to a backend. An error
+/// message returned by a handler fails the compilation.
+#[derive(Default, Clone)]
+pub struct Directives {
+    block: HashMap<String, Arc<BlockHandler>>,
+    inline: HashMap<String, Arc<InlineHandler>>,
+}
+
+impl Directives {
+    /// Registers a handler for leaf and container directives named `name`.
+    pub fn register_block<F>(&mut self, name: impl Into<String>, handler: F)
+    where
+        F: for<'a> Fn(Directive<'a>) -> Result<Vec<hir::Statement<'a>>, String>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.block.insert(name.into(), Arc::new(handler));
+    }
+
+    /// Registers a handler for inline directives named `name`.
+    pub fn register_inline<F>(&mut self, name: impl Into<String>, handler: F)
+    where
+        F: for<'a> Fn(Directive<'a>) -> Result<Vec<hir::TextSegment<'a>>, String>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.inline.insert(name.into(), Arc::new(handler));
+    }
+
+    pub(crate) fn block(&self, name: &str) -> Option<&BlockHandler> {
+        self.block.get(name).map(|handler| &**handler)
+    }
+
+    pub(crate) fn inline(&self, name: &str) -> Option<&InlineHandler> {
+        self.inline.get(name).map(|handler| &**handler)
+    }
+}
+
+impl fmt::Debug for Directives {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Directives")
+            .field("block", &self.block.keys().collect::<Vec<_>>())
+            .field("inline", &self.inline.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
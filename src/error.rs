@@ -9,6 +9,8 @@ where
     B: std::fmt::Debug + Backend,
     B::Error: std::error::Error,
 {
+    #[error("include error")]
+    Include(#[from] parser::include::Error),
     #[error("lir parsing error")]
     LirParser(#[from] parser::lir::Error),
     #[error("hir parsing error")]
@@ -1,11 +1,22 @@
 mod ast;
 mod backend;
+mod bibliography;
+mod directive;
 mod error;
+mod options;
 mod parser;
 
 pub use self::{
+    ast::{hir, Metadata},
     backend::Backend,
+    bibliography::{Bibliography, CitationStyle, Entry, Name},
+    directive::{Directive, DirectiveKind, Directives},
     error::{Error, Result},
+    options::Options,
+    parser::{
+        bibliography::Error as BibliographyError,
+        include::{FileLoader, FsLoader},
+    },
 };
 
 #[cfg(feature = "html-backend")]
@@ -16,10 +27,30 @@ pub use self::backend::PdfBackend;
 
 #[cfg(feature = "html-backend")]
 pub fn compile_html(source_code: &str) -> Result<String, HtmlBackend> {
-    Backend::compile(source_code)
+    Backend::compile(source_code, &Options::default())
+}
+
+/// Compiles the file at `path`, loading included files from the file system.
+#[cfg(feature = "html-backend")]
+pub fn compile_html_file(path: impl AsRef<std::path::Path>) -> Result<String, HtmlBackend> {
+    Backend::compile_file(path.as_ref(), &FsLoader::default(), &Options::default())
+}
+
+/// Compiles a document into a complete HTML page using its front matter.
+#[cfg(feature = "html-backend")]
+pub fn compile_html_page(source_code: &str) -> Result<String, HtmlBackend> {
+    let doc_lir = parser::lir::parse(source_code)?;
+    let doc_hir = parser::hir::parse(&doc_lir, &Options::default())?;
+    let body = HtmlBackend::compile_hir(&doc_hir).map_err(Error::Backend)?;
+    Ok(HtmlBackend::page(&doc_hir.metadata, &body))
 }
 
 #[cfg(feature = "pdf-backend")]
 pub fn compile_pdf(source_code: &str) -> Result<std::convert::Infallible, PdfBackend> {
-    Backend::compile(source_code)
+    Backend::compile(source_code, &Options::default())
+}
+
+/// Reads the front matter of a document without compiling the rest of it.
+pub fn metadata(source_code: &str) -> Metadata<'_> {
+    parser::lir::parse_metadata(source_code)
 }
@@ -0,0 +1,25 @@
+use crate::{
+    bibliography::{Bibliography, CitationStyle},
+    directive::Directives,
+};
+use std::collections::HashMap;
+
+/// Settings for compiling a document.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// Variables available to `{{name}}` substitutions. They take precedence
+    /// over variables defined in the document with `@set`.
+    pub variables: HashMap<String, String>,
+    /// Works available to `[@key]` citations.
+    pub bibliography: Bibliography,
+    pub citation_style: CitationStyle,
+    /// Rejects raw blocks, event handler attributes like `onclick`, `style`
+    /// attributes and `javascript:` or `vbscript:` URLs, which would let
+    /// untrusted input inject markup, styles or scripts.
+    pub forbid_raw: bool,
+    /// Handlers for custom directives.
+    pub directives: Directives,
+    /// Flags tested by `@if` conditions, like `edition` in
+    /// `@if edition == "pro"`. Conditions using other flags are errors.
+    pub flags: HashMap<String, String>,
+}
@@ -0,0 +1,484 @@
+use crate::bibliography::{Entry, Name};
+use pest::{iterators::Pair, Parser};
+use std::{io, path::PathBuf};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("parsing error")]
+    Pest(#[from] Box<pest::error::Error<Rule>>),
+    #[error("cannot read bibliography")]
+    Io(#[from] io::Error),
+    #[error("unknown bibliography format of `{}`", .0.display())]
+    UnknownFormat(PathBuf),
+    #[error("bibliography key `{0}` is used more than once")]
+    DuplicateKey(String),
+    #[error("invalid CSL-JSON: {0}")]
+    InvalidCslJson(&'static str),
+}
+
+impl From<pest::error::Error<Rule>> for Error {
+    fn from(error: pest::error::Error<Rule>) -> Self {
+        Error::Pest(Box::new(error))
+    }
+}
+
+#[derive(pest_derive::Parser)]
+#[grammar = "bibliography.pest"]
+struct BibliographyParser;
+
+pub fn parse_bibtex(code: &str) -> Result<Vec<Entry>> {
+    let bibtex = BibliographyParser::parse(Rule::bibtex, code)?
+        .next()
+        .unwrap();
+
+    Ok(bibtex
+        .into_inner()
+        .filter(|pair| pair.as_rule() == Rule::bibtex_entry)
+        .map(bibtex_entry)
+        .collect())
+}
+
+fn bibtex_entry(pair: Pair<'_, Rule>) -> Entry {
+    let mut pairs = pair.into_inner();
+    pairs.next().unwrap(); // Entry type
+    let mut entry = Entry {
+        key: pairs.next().unwrap().as_str().to_string(),
+        ..Entry::default()
+    };
+
+    for field in pairs {
+        let mut pairs = field.into_inner();
+        let name = pairs.next().unwrap().as_str().to_ascii_lowercase();
+        let value = bibtex_value(pairs.next().unwrap());
+        let text = Some(latex_to_text(&value));
+
+        match name.as_str() {
+            "author" => {
+                entry.authors = split_top_level(&value, " and ")
+                    .into_iter()
+                    .map(bibtex_name)
+                    .collect()
+            }
+            "title" => entry.title = text,
+            "journal" | "journaltitle" | "booktitle" => {
+                entry.container_title = entry.container_title.or(text)
+            }
+            "volume" => entry.volume = text,
+            "pages" => entry.pages = text,
+            "publisher" | "institution" | "school" | "organization" => {
+                entry.publisher = entry.publisher.or(text)
+            }
+            "year" => entry.year = text,
+            // biblatex dates look like `2020-03-01`
+            "date" if entry.year.is_none() => {
+                entry.year = text.map(|date| date.chars().take(4).collect())
+            }
+            "doi" => entry.doi = text,
+            "url" => entry.url = text,
+            _ => (),
+        }
+    }
+
+    entry
+}
+
+/// Concatenates the parts of a value, keeping braces for splitting names.
+fn bibtex_value(pair: Pair<'_, Rule>) -> String {
+    let mut value = String::new();
+    for part in pair.into_inner() {
+        match part.as_rule() {
+            Rule::bibtex_braced | Rule::bibtex_quoted => {
+                value += part.into_inner().next().unwrap().as_str()
+            }
+            Rule::bibtex_word => value += part.as_str(),
+            _ => unreachable!(),
+        }
+    }
+    // Line breaks within values are insignificant
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Parses `Family, Given`, `Given von Family` or `{Organization}`.
+fn bibtex_name(name: &str) -> Name {
+    let parts = split_top_level(name, ",");
+    if parts.len() > 1 {
+        return Name {
+            family: latex_to_text(parts[0]),
+            given: Some(latex_to_text(parts[parts.len() - 1])).filter(|given| !given.is_empty()),
+        };
+    }
+
+    let words = split_top_level(name, " ");
+    let last = words.len() - 1;
+    // The family name starts at the first lowercase particle like `van`
+    let family_start = words[..last]
+        .iter()
+        .position(|word| word.starts_with(|c: char| c.is_lowercase()))
+        .unwrap_or(last);
+
+    Name {
+        family: latex_to_text(&words[family_start..].join(" ")),
+        given: Some(latex_to_text(&words[..family_start].join(" ")))
+            .filter(|given| !given.is_empty()),
+    }
+}
+
+/// Splits `value` at `separator`s outside of braces.
+fn split_top_level<'s>(value: &'s str, separator: &str) -> Vec<&'s str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (idx, c) in value.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ if depth == 0 && idx >= start && value[idx..].starts_with(separator) => {
+                parts.push(value[start..idx].trim());
+                start = idx + separator.len();
+            }
+            _ => (),
+        }
+    }
+    parts.push(value[start..].trim());
+    parts
+}
+
+/// Turns the LaTeX found in BibTeX values into plain text.
+fn latex_to_text(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' => (),
+            '~' => text.push('\u{a0}'),
+            '-' if chars.peek() == Some(&'-') => {
+                chars.next();
+                if chars.peek() == Some(&'-') {
+                    chars.next();
+                    text.push('—');
+                } else {
+                    text.push('–');
+                }
+            }
+            '\\' => match chars.next() {
+                Some(c) if c.is_ascii_alphabetic() => {
+                    let mut command = c.to_string();
+                    while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                        command.push(c);
+                        chars.next();
+                    }
+                    // Control words swallow the spaces after them
+                    while chars.peek() == Some(&' ') {
+                        chars.next();
+                    }
+
+                    if let Some(mark) = combining_mark(&command) {
+                        push_accented(&mut chars, mark, &mut text);
+                    } else if let Some(letter) = special_letter(&command) {
+                        text.push_str(letter);
+                    }
+                    // Other commands are dropped, keeping their arguments
+                }
+                Some(c) => match combining_mark(&c.to_string()) {
+                    Some(mark) => push_accented(&mut chars, mark, &mut text),
+                    None => text.push(c),
+                },
+                None => (),
+            },
+            c if c.is_whitespace() => {
+                if !text.ends_with(' ') {
+                    text.push(' ');
+                }
+            }
+            c => text.push(c),
+        }
+    }
+    text.trim().to_string()
+}
+
+/// Pushes the letter following an accent command, as in `\'{e}` or `\"o`.
+fn push_accented(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    mark: char,
+    text: &mut String,
+) {
+    while chars.peek() == Some(&'{') {
+        chars.next();
+    }
+    match chars.next() {
+        // Dotless letters, as in `\'{\i}`
+        Some('\\') => text.extend(chars.next()),
+        Some(letter) => text.push(letter),
+        None => (),
+    }
+    text.push(mark);
+}
+
+fn combining_mark(command: &str) -> Option<char> {
+    Some(match command {
+        "`" => '\u{300}',
+        "'" => '\u{301}',
+        "^" => '\u{302}',
+        "~" => '\u{303}',
+        "=" => '\u{304}',
+        "u" => '\u{306}',
+        "." => '\u{307}',
+        "\"" => '\u{308}',
+        "H" => '\u{30b}',
+        "v" => '\u{30c}',
+        "c" => '\u{327}',
+        _ => return None,
+    })
+}
+
+fn special_letter(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "ss" => "ß",
+        "ae" => "æ",
+        "AE" => "Æ",
+        "oe" => "œ",
+        "OE" => "Œ",
+        "aa" => "å",
+        "AA" => "Å",
+        "o" => "ø",
+        "O" => "Ø",
+        "l" => "ł",
+        "L" => "Ł",
+        "i" => "ı",
+        "j" => "ȷ",
+        _ => return None,
+    })
+}
+
+pub fn parse_csl_json(code: &str) -> Result<Vec<Entry>> {
+    let json = BibliographyParser::parse(Rule::json, code)?.next().unwrap();
+    match Json::parse(json.into_inner().next().unwrap())? {
+        Json::Array(items) => items.iter().map(csl_entry).collect(),
+        _ => Err(Error::InvalidCslJson("expected an array of items")),
+    }
+}
+
+fn csl_entry(item: &Json) -> Result<Entry> {
+    let text = |name| item.get(name).and_then(Json::as_text);
+
+    let key = text("id").ok_or(Error::InvalidCslJson("item without an `id`"))?;
+    let authors = match item.get("author") {
+        Some(Json::Array(authors)) => authors.iter().filter_map(csl_name).collect(),
+        _ => vec![],
+    };
+    let year = item.get("issued").and_then(|issued| {
+        match issued.get("date-parts") {
+            // `[[2020, 3, 1]]`
+            Some(Json::Array(parts)) => match parts.first() {
+                Some(Json::Array(parts)) => parts.first().and_then(Json::as_text),
+                _ => None,
+            },
+            _ => issued
+                .get("literal")
+                .or_else(|| issued.get("raw"))?
+                .as_text(),
+        }
+    });
+
+    Ok(Entry {
+        key,
+        authors,
+        title: text("title"),
+        container_title: text("container-title"),
+        volume: text("volume"),
+        pages: text("page"),
+        publisher: text("publisher"),
+        year,
+        doi: text("DOI"),
+        url: text("URL"),
+    })
+}
+
+fn csl_name(name: &Json) -> Option<Name> {
+    match name.get("family").or_else(|| name.get("literal")) {
+        Some(family) => Some(Name {
+            family: family.as_text()?,
+            given: name.get("given").and_then(Json::as_text),
+        }),
+        None => None,
+    }
+}
+
+/// Just enough JSON for CSL-JSON.
+#[derive(Debug)]
+enum Json<'a> {
+    Object(Vec<(String, Json<'a>)>),
+    Array(Vec<Json<'a>>),
+    String(String),
+    Number(&'a str),
+    /// `true`, `false` and `null`, which CSL-JSON does not use.
+    Other,
+}
+
+impl<'a> Json<'a> {
+    fn parse(pair: Pair<'a, Rule>) -> Result<Self> {
+        Ok(match pair.as_rule() {
+            Rule::json_object => Json::Object(
+                pair.into_inner()
+                    .map(|member| {
+                        let mut pairs = member.into_inner();
+                        let name = unescape(pairs.next().unwrap())?;
+                        Ok((name, Json::parse(pairs.next().unwrap())?))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            Rule::json_array => {
+                Json::Array(pair.into_inner().map(Json::parse).collect::<Result<_>>()?)
+            }
+            Rule::json_string => Json::String(unescape(pair)?),
+            Rule::json_number => Json::Number(pair.as_str()),
+            Rule::json_literal => Json::Other,
+            _ => unreachable!(),
+        })
+    }
+
+    fn get(&self, name: &str) -> Option<&Json<'a>> {
+        match self {
+            Json::Object(members) => members
+                .iter()
+                .find(|(name_, _)| name_ == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Strings and numbers, which CSL-JSON uses interchangeably for fields
+    /// like `volume`.
+    fn as_text(&self) -> Option<String> {
+        match self {
+            Json::String(text) => Some(text.clone()),
+            Json::Number(number) => Some(number.to_string()),
+            _ => None,
+        }
+    }
+}
+
+fn unescape(string: Pair<'_, Rule>) -> Result<String> {
+    let content = string.into_inner().next().unwrap().as_str();
+
+    let mut unescaped = String::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        unescaped.push(match chars.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = code_unit(&mut chars)?;
+                let mut code_units = vec![high];
+                // Characters outside the BMP are escaped as surrogate pairs
+                if (0xd800..0xdc00).contains(&high) && chars.as_str().starts_with("\\u") {
+                    chars.nth(1);
+                    code_units.push(code_unit(&mut chars)?);
+                }
+                for c in std::char::decode_utf16(code_units) {
+                    unescaped.push(c.map_err(|_| Error::InvalidCslJson("invalid unicode escape"))?);
+                }
+                continue;
+            }
+            _ => return Err(Error::InvalidCslJson("invalid string escape")),
+        });
+    }
+    Ok(unescaped)
+}
+
+/// Reads the four hex digits of a `\u` escape.
+fn code_unit(chars: &mut std::str::Chars<'_>) -> Result<u16> {
+    let hex = chars.take(4).collect::<String>();
+    u16::from_str_radix(&hex, 16)
+        .ok()
+        .filter(|_| hex.len() == 4)
+        .ok_or(Error::InvalidCslJson("invalid unicode escape"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bibtex() {
+        let code = r#"
+Ignored text
+@comment{ignored}
+@article{doe2020,
+  author = {Doe, Jane and Ludwig van Beethoven and {World Health Organization}},
+  title = "The {RNA} of Caf\'{e}s",
+  journal = {Journal of} # " Tests",
+  year = 2020,
+  pages = {1--10},
+}
+"#;
+        let expected = vec![Entry {
+            key: "doe2020".into(),
+            authors: vec![
+                Name {
+                    family: "Doe".into(),
+                    given: Some("Jane".into()),
+                },
+                Name {
+                    family: "van Beethoven".into(),
+                    given: Some("Ludwig".into()),
+                },
+                Name {
+                    family: "World Health Organization".into(),
+                    given: None,
+                },
+            ],
+            title: Some("The RNA of Cafe\u{301}s".into()),
+            container_title: Some("Journal of Tests".into()),
+            pages: Some("1–10".into()),
+            year: Some("2020".into()),
+            ..Entry::default()
+        }];
+        assert_eq!(parse_bibtex(code).unwrap(), expected);
+    }
+
+    #[test]
+    fn csl_json() {
+        let code = r#"[{
+            "id": "roe2019",
+            "type": "book",
+            "author": [{"family": "Roe", "given": "Richard"}, {"literal": "ACME"}],
+            "title": "On \"Quotes\" \u00e9 \ud83d\ude00",
+            "volume": 3,
+            "issued": {"date-parts": [[2019, 5]]}
+        }]"#;
+        let expected = vec![Entry {
+            key: "roe2019".into(),
+            authors: vec![
+                Name {
+                    family: "Roe".into(),
+                    given: Some("Richard".into()),
+                },
+                Name {
+                    family: "ACME".into(),
+                    given: None,
+                },
+            ],
+            title: Some("On \"Quotes\" é 😀".into()),
+            volume: Some("3".into()),
+            year: Some("2019".into()),
+            ..Entry::default()
+        }];
+        assert_eq!(parse_csl_json(code).unwrap(), expected);
+
+        assert!(parse_csl_json(r#"{"id": "x"}"#).is_err());
+        assert!(parse_csl_json(r#"[{"title": "x"}]"#).is_err());
+    }
+}
@@ -1,45 +1,503 @@
-use crate::ast::{hir, lir};
+use crate::{
+    ast::{hir, lir},
+    bibliography::Bibliography,
+    directive::{Directive, DirectiveKind, Directives},
+    options::Options,
+};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, thiserror::Error)]
-pub enum Error {}
+pub enum Error {
+    #[error("footnote `{0}` is referenced but never defined")]
+    UndefinedFootnote(String),
+    #[error("footnote `{0}` is defined but never referenced")]
+    UnusedFootnote(String),
+    #[error("footnote `{0}` is defined more than once")]
+    DuplicateFootnoteDefinition(String),
+    #[error("directive `{0}` is not registered")]
+    UnregisteredDirective(String),
+    #[error("directive `{name}` failed: {message}")]
+    Directive { name: String, message: String },
+    #[error("include of `{0}` can only be resolved when compiling a file")]
+    UnresolvedInclude(String),
+    #[error("variable `{0}` is used but never defined")]
+    UndefinedVariable(String),
+    #[error("ID `{0}` is given to more than one element")]
+    DuplicateId(String),
+    #[error("ID `{0}` starts with a prefix reserved for footnotes or bibliography entries")]
+    ReservedId(String),
+    #[error("cross-reference to undefined ID `{0}`")]
+    UndefinedCrossReference(String),
+    #[error("citation of `{0}`, which is not in the bibliography")]
+    UndefinedCitation(String),
+    #[error("raw block for `{0}` is forbidden")]
+    ForbiddenRaw(String),
+    #[error("attribute `{0}` is forbidden")]
+    ForbiddenAttribute(String),
+    #[error("attribute `{0}` is given more than once")]
+    DuplicateAttribute(String),
+    #[error("URL `{0}` is forbidden")]
+    ForbiddenUrl(String),
+    #[error("attributes must be followed by a header, paragraph or list")]
+    DanglingAttributes,
+    #[error("flag `{0}` is used in a condition but never set")]
+    UnknownFlag(String),
+    #[error("`{0}` is not part of an `@if` block")]
+    UnmatchedConditional(&'static str),
+    #[error("`@if` is never closed by `@end`")]
+    UnclosedConditional,
+    #[error("definitions must directly follow their term")]
+    DefinitionWithoutTerm,
+    #[error("table separator rows may only follow the first row")]
+    MisplacedTableSeparator,
+    #[error("`:::{0}` is never closed by `:::`")]
+    UnclosedContainer(String),
+    #[error("`:::` without a container to close")]
+    UnmatchedContainerEnd,
+}
+
+pub fn parse<'a>(document: &lir::Document<'a>, options: &Options) -> Result<hir::Document<'a>> {
+    // Excluded content is dropped before anything else sees it
+    let statements_lir = resolve_conditionals(&document.statements, &options.flags)?;
+
+    let mut ctx = Context {
+        variable_overrides: options.variables.clone(),
+        bibliography: options.bibliography.clone(),
+        citation_style: options.citation_style,
+        forbid_raw: options.forbid_raw,
+        directives: options.directives.clone(),
+        ..Context::default()
+    };
+
+    let mut statements = parse_statements(&mut ctx, &statements_lir)?;
+    let mut footnotes = ctx.footnotes()?;
+    ctx.register_ids(&mut statements, &footnotes)?;
+    ctx.resolve_cross_references(&mut statements, &mut footnotes)?;
+    ctx.expand_tables_of_contents(&mut statements);
+    ctx.expand_bibliographies(&mut statements);
+
+    Ok(hir::Document {
+        metadata: document.metadata.clone(),
+        statements,
+        footnotes,
+    })
+}
+
+/// State collected while walking the document.
+#[derive(Debug, Default)]
+struct Context<'a> {
+    /// Footnote labels in order of their first reference, with the number of
+    /// references seen so far.
+    footnote_references: Vec<(&'a str, usize)>,
+    /// Footnote definitions in source order.
+    footnote_definitions: Vec<(&'a str, hir::Text<'a>)>,
+    /// Variables defined in the document so far.
+    variables: HashMap<&'a str, &'a str>,
+    /// Variables supplied by the caller, taking precedence over `variables`.
+    variable_overrides: HashMap<String, String>,
+    /// Elements with an ID, headers in document order.
+    targets: Vec<Target<'a>>,
+    /// IDs targeted by cross-references.
+    cross_references: Vec<&'a str>,
+    bibliography: Bibliography,
+    citation_style: hir::CitationStyle,
+    /// Cited keys in order of their first citation.
+    citations: Vec<&'a str>,
+    forbid_raw: bool,
+    directives: Directives,
+}
+
+/// Prefixes of the IDs that backends give to footnotes and bibliography entries.
+const RESERVED_ID_PREFIXES: [&str; 3] = ["fn-", "fnref-", "ref-"];
+
+/// An element as seen by cross-references and tables of contents.
+#[derive(Debug)]
+struct Target<'a> {
+    /// Set for headers only.
+    header_type: Option<hir::HeaderType>,
+    id: String,
+    /// Text that cross-references show, without links or footnote references.
+    label: Vec<hir::TextSegment<'a>>,
+}
+
+impl<'a> Context<'a> {
+    fn reference_footnote(&mut self, label: &'a str) -> hir::TextSegment<'a> {
+        let number = match self
+            .footnote_references
+            .iter()
+            .position(|(label_, _)| *label_ == label)
+        {
+            Some(idx) => idx,
+            None => {
+                self.footnote_references.push((label, 0));
+                self.footnote_references.len() - 1
+            }
+        };
+
+        let references = &mut self.footnote_references[number].1;
+        *references += 1;
+
+        hir::TextSegment::FootnoteReference {
+            number: number + 1,
+            occurrence: *references,
+        }
+    }
+
+    fn define_footnote(&mut self, label: &'a str, text: hir::Text<'a>) -> Result<()> {
+        if self
+            .footnote_definitions
+            .iter()
+            .any(|(label_, _)| *label_ == label)
+        {
+            return Err(Error::DuplicateFootnoteDefinition(label.to_string()));
+        }
+
+        self.footnote_definitions.push((label, text));
+        Ok(())
+    }
+
+    fn variable(&self, name: &str) -> Result<Cow<'a, str>> {
+        if let Some(value) = self.variable_overrides.get(name) {
+            return Ok(Cow::Owned(value.clone()));
+        }
+
+        match self.variables.get(name) {
+            Some(value) => Ok(Cow::Borrowed(value)),
+            None => Err(Error::UndefinedVariable(name.to_string())),
+        }
+    }
+
+    /// Registers the ID of every header, block and span, including those made
+    /// by directives. Headers without an explicit ID get one generated from
+    /// their text, once all explicit IDs are known.
+    fn register_ids(
+        &mut self,
+        statements: &mut [hir::Statement<'a>],
+        footnotes: &[hir::Footnote<'a>],
+    ) -> Result<()> {
+        let mut explicit_ids = Vec::new();
+        let mut targets = Vec::new();
+
+        visit_statements(statements, &mut |statement| {
+            let (id, label) = match statement {
+                hir::Statement::Header(header) => {
+                    if !header.id.is_empty() {
+                        explicit_ids.push(header.id.clone());
+                    }
+                    return;
+                }
+                hir::Statement::Paragraph(hir::ParagraphStatement { attributes, .. })
+                | hir::Statement::List(hir::ListStatement { attributes, .. })
+                | hir::Statement::DefinitionList(hir::DefinitionListStatement {
+                    attributes, ..
+                }) => (attributes.id, None),
+                hir::Statement::Admonition(hir::AdmonitionStatement {
+                    attributes, title, ..
+                }) => (attributes.id, title.as_ref()),
+                _ => return,
+            };
+
+            if let Some(id) = id {
+                explicit_ids.push(id.to_string());
+                targets.push(Target {
+                    header_type: None,
+                    id: id.to_string(),
+                    label: match label {
+                        Some(title) => target_label(&title.segments),
+                        None => vec![hir::TextSegment::Text(Cow::Owned(id.to_string()))],
+                    },
+                });
+            }
+        });
+
+        let mut span_targets = Vec::new();
+        visit_texts(statements, &mut |segments| {
+            collect_span_targets(segments, &mut span_targets)
+        });
+        for footnote in footnotes {
+            collect_span_targets(&footnote.text.segments, &mut span_targets);
+        }
+        explicit_ids.extend(span_targets.iter().map(|target| target.id.clone()));
+        targets.extend(span_targets);
+
+        let mut ids = HashSet::new();
+        for id in explicit_ids {
+            if RESERVED_ID_PREFIXES
+                .iter()
+                .any(|prefix| id.starts_with(prefix))
+            {
+                return Err(Error::ReservedId(id));
+            }
+            if ids.contains(&id) {
+                return Err(Error::DuplicateId(id));
+            }
+            ids.insert(id);
+        }
+
+        visit_statements(statements, &mut |statement| {
+            if let hir::Statement::Header(header) = statement {
+                if header.id.is_empty() {
+                    let mut slug = String::new();
+                    slugify(&header.text.segments, &mut slug);
+                    if slug.is_empty()
+                        || RESERVED_ID_PREFIXES
+                            .iter()
+                            .any(|prefix| slug.starts_with(prefix))
+                    {
+                        slug.insert_str(
+                            0,
+                            if slug.is_empty() {
+                                "section"
+                            } else {
+                                "section-"
+                            },
+                        );
+                    }
+
+                    // Repeated headers get a counter appended
+                    let mut id = slug.clone();
+                    let mut counter = 0;
+                    while ids.contains(&id) {
+                        counter += 1;
+                        id = format!("{}-{}", slug, counter);
+                    }
+                    ids.insert(id.clone());
+                    header.id = id;
+                }
+
+                targets.push(Target {
+                    header_type: Some(header.header_type),
+                    id: header.id.clone(),
+                    label: target_label(&header.text.segments),
+                });
+            }
+        });
+
+        // Cross-references within a label show the label of their target
+        let labels = targets
+            .iter()
+            .map(|target| inline_cross_references(&target.label, Some(&targets)))
+            .collect::<Vec<_>>();
+        for (target, label) in targets.iter_mut().zip(labels) {
+            target.label = label;
+        }
+
+        self.targets = targets;
+        Ok(())
+    }
+
+    /// Fills in the text of every cross-reference, once all targets are known.
+    fn resolve_cross_references(
+        &self,
+        statements: &mut [hir::Statement<'a>],
+        footnotes: &mut [hir::Footnote<'a>],
+    ) -> Result<()> {
+        for id in &self.cross_references {
+            if !self.targets.iter().any(|target| target.id == *id) {
+                return Err(Error::UndefinedCrossReference(id.to_string()));
+            }
+        }
+
+        let mut resolve = |segments: &mut Vec<hir::TextSegment<'a>>| {
+            resolve_cross_references(segments, &self.targets)
+        };
+        visit_texts(statements, &mut resolve);
+        for footnote in footnotes {
+            resolve(&mut footnote.text.segments);
+        }
+
+        Ok(())
+    }
+
+    /// Fills in the lists of all tables of contents, once all headers are known.
+    fn expand_tables_of_contents(&self, statements: &mut [hir::Statement<'a>]) {
+        visit_statements(statements, &mut |statement| {
+            if let hir::Statement::TableOfContents(toc) = statement {
+                let headers = self
+                    .targets
+                    .iter()
+                    .filter(|target| match target.header_type {
+                        Some(header_type) => (toc.min_level..=toc.max_level).contains(&header_type),
+                        None => false,
+                    })
+                    .collect::<Vec<_>>();
+                toc.list = toc_list(&headers);
+            }
+        });
+    }
+
+    fn cite(&mut self, citation: &lir::Citation<'a>) -> Result<hir::CitationItem<'a>> {
+        let entry = self
+            .bibliography
+            .get(citation.key)
+            .ok_or_else(|| Error::UndefinedCitation(citation.key.to_string()))?;
+
+        let number = match self.citations.iter().position(|key| *key == citation.key) {
+            Some(idx) => idx + 1,
+            None => {
+                self.citations.push(citation.key);
+                self.citations.len()
+            }
+        };
+
+        Ok(hir::CitationItem {
+            number,
+            entry: entry.clone(),
+            locator: citation.locator,
+        })
+    }
+
+    /// Fills in the entries of all bibliographies, once all citations are known.
+    fn expand_bibliographies(&self, statements: &mut [hir::Statement<'a>]) {
+        let mut entries = self
+            .citations
+            .iter()
+            .map(|key| self.bibliography.get(key).unwrap().clone())
+            .collect::<Vec<_>>();
+        if self.citation_style == hir::CitationStyle::AuthorYear {
+            // Works without authors are sorted by their title instead
+            entries.sort_by_cached_key(|entry| {
+                let name = entry.authors.first().map(|author| &author.family);
+                (
+                    name.or(entry.title.as_ref())
+                        .map(|name| name.to_lowercase()),
+                    entry.year.clone(),
+                )
+            });
+        }
+
+        visit_statements(statements, &mut |statement| {
+            if let hir::Statement::Bibliography(bibliography) = statement {
+                bibliography.entries = entries.clone();
+            }
+        });
+    }
+
+    fn footnotes(&mut self) -> Result<Vec<hir::Footnote<'a>>> {
+        let mut footnotes = Vec::new();
+
+        for (label, references) in &self.footnote_references {
+            let idx = self
+                .footnote_definitions
+                .iter()
+                .position(|(label_, _)| label_ == label)
+                .ok_or_else(|| Error::UndefinedFootnote(label.to_string()))?;
+
+            footnotes.push(hir::Footnote {
+                label,
+                text: self.footnote_definitions.remove(idx).1,
+                references: *references,
+            });
+        }
+
+        if let Some((label, _)) = self.footnote_definitions.first() {
+            return Err(Error::UnusedFootnote(label.to_string()));
+        }
+
+        Ok(footnotes)
+    }
+}
 
-pub fn parse<'a>(document: &lir::Document<'a>) -> Result<hir::Document<'a>> {
+fn parse_statements<'a>(
+    ctx: &mut Context<'a>,
+    statements_lir: &[lir::Statement<'a>],
+) -> Result<Vec<hir::Statement<'a>>> {
     let mut statements = Vec::new();
+    // Attributes waiting for the statement they belong to
+    let mut block_attributes: Option<lir::Attributes<'a>> = None;
+    // Open containers with their parsed title and the statements around them
+    let mut containers = Vec::new();
 
     let mut idx = 0;
-    while idx < document.statements.len() {
-        match &document.statements[idx] {
+    while idx < statements_lir.len() {
+        match &statements_lir[idx] {
+            lir::Statement::Attributes(lir::AttributesStatement { attributes }) => {
+                block_attributes
+                    .get_or_insert_with(Default::default)
+                    .extend(attributes);
+                idx += 1;
+                continue;
+            }
+            lir::Statement::Conditional(conditional) => {
+                return Err(Error::UnmatchedConditional(conditional_keyword(
+                    conditional,
+                )));
+            }
             lir::Statement::EmptyLine(_) => idx += 1,
-            lir::Statement::Comment(_) => idx += 1,
+            // Comments neither split paragraphs, lists or tables nor take
+            // block attributes
+            lir::Statement::Comment(_) => {
+                idx += 1;
+                continue;
+            }
             lir::Statement::Header(header_stmt) => {
-                statements.push(hir::Statement::Header(parse_header_statement(header_stmt)?));
+                statements.push(hir::Statement::Header(parse_header_statement(
+                    ctx,
+                    header_stmt,
+                    block_attributes.take().unwrap_or_default(),
+                )?));
                 idx += 1;
             }
             lir::Statement::Paragraph(paragraph_stmt) => {
                 let mut paragraphs = vec![paragraph_stmt];
+                let mut last = idx;
                 idx += 1;
 
-                while idx < document.statements.len() {
-                    if let lir::Statement::Paragraph(paragraph_stmt) = &document.statements[idx] {
-                        paragraphs.push(paragraph_stmt);
-                        idx += 1;
-                    } else {
-                        break;
+                while idx < statements_lir.len() {
+                    match &statements_lir[idx] {
+                        lir::Statement::Paragraph(paragraph_stmt) => {
+                            paragraphs.push(paragraph_stmt);
+                            last = idx;
+                            idx += 1;
+                        }
+                        lir::Statement::Comment(_) => idx += 1,
+                        _ => break,
                     }
                 }
 
-                statements.push(hir::Statement::Paragraph(parse_paragraph_statement(
-                    &paragraphs,
+                // The last line before a definition is its term
+                let term = match statements_lir.get(idx) {
+                    Some(lir::Statement::Definition(_)) => paragraphs.pop().map(|_| last),
+                    _ => None,
+                };
+
+                if !paragraphs.is_empty() {
+                    statements.push(hir::Statement::Paragraph(parse_paragraph_statement(
+                        ctx,
+                        &paragraphs,
+                        block_attributes.take().unwrap_or_default(),
+                    )?));
+                }
+
+                if let Some(start) = term {
+                    idx = definition_list_end(statements_lir, idx);
+                    statements.push(hir::Statement::DefinitionList(parse_definition_list(
+                        ctx,
+                        &statements_lir[start..idx],
+                        block_attributes.take().unwrap_or_default(),
+                    )?));
+                }
+            }
+            lir::Statement::Definition(_) => {
+                let start = idx;
+                idx = definition_list_end(statements_lir, idx);
+                statements.push(hir::Statement::DefinitionList(parse_definition_list(
+                    ctx,
+                    &statements_lir[start..idx],
+                    block_attributes.take().unwrap_or_default(),
                 )?));
             }
             lir::Statement::ListItem(list_item_stmt) => {
                 let mut list_statements = vec![Either::Left(list_item_stmt)];
                 idx += 1;
 
-                while idx < document.statements.len() {
-                    match &document.statements[idx] {
+                while idx < statements_lir.len() {
+                    match &statements_lir[idx] {
                         lir::Statement::ListItem(list_item_stmt) => {
                             list_statements.push(Either::Left(list_item_stmt));
                             idx += 1;
@@ -48,22 +506,196 @@ pub fn parse<'a>(document: &lir::Document<'a>) -> Result<hir::Document<'a>> {
                             list_statements.push(Either::Right(paragraph_stmt));
                             idx += 1;
                         }
+                        lir::Statement::Comment(_) => idx += 1,
                         _ => break,
                     }
                 }
 
-                statements.push(hir::Statement::List(hir::ListStatement {
-                    list: parse_list(&list_statements)?,
-                }));
+                // Block attributes go to the first of the lists
+                let mut attributes = Some(check_attributes(
+                    ctx,
+                    block_attributes.take().unwrap_or_default(),
+                )?);
+                for list in parse_lists(ctx, &list_statements)? {
+                    statements.push(hir::Statement::List(hir::ListStatement {
+                        attributes: attributes.take().unwrap_or_default(),
+                        list,
+                    }));
+                }
             }
             lir::Statement::HorizontalRule(_) => {
                 statements.push(hir::Statement::HorizontalRule(hir::HorizontalRuleStatement));
                 idx += 1;
             }
+            lir::Statement::CodeBlock(lir::CodeBlockStatement { info, content }) => {
+                statements.push(hir::Statement::CodeBlock(hir::CodeBlockStatement {
+                    info: *info,
+                    content: content.clone(),
+                }));
+                idx += 1;
+            }
+            lir::Statement::Raw(lir::RawStatement { target, content }) => {
+                if ctx.forbid_raw {
+                    return Err(Error::ForbiddenRaw(target.to_string()));
+                }
+                statements.push(hir::Statement::Raw(hir::RawStatement {
+                    target,
+                    content: content.clone(),
+                }));
+                idx += 1;
+            }
+            lir::Statement::MathBlock(lir::MathBlockStatement { content }) => {
+                statements.push(hir::Statement::MathBlock(hir::MathBlockStatement {
+                    content: content.clone(),
+                }));
+                idx += 1;
+            }
+            lir::Statement::Figure(figure_stmt) => {
+                statements.push(hir::Statement::Figure(parse_figure_statement(
+                    ctx,
+                    figure_stmt,
+                )?));
+                idx += 1;
+            }
+            lir::Statement::FootnoteDefinition(lir::FootnoteDefinitionStatement {
+                label,
+                text,
+            }) => {
+                let mut segments = text.segments.clone();
+                idx += 1;
+
+                loop {
+                    match statements_lir.get(idx) {
+                        Some(lir::Statement::Paragraph(paragraph_stmt)) => {
+                            segments.push(lir::TextSegment::Break);
+                            segments.extend(paragraph_stmt.text.segments.clone());
+                        }
+                        Some(lir::Statement::Comment(_)) => (),
+                        _ => break,
+                    }
+                    idx += 1;
+                }
+
+                let text = hir::Text {
+                    segments: parse_text_segments(ctx, &segments)?,
+                };
+                ctx.define_footnote(label, text)?;
+            }
+            lir::Statement::TableRow(_) | lir::Statement::TableSeparator(_) => {
+                let start = idx;
+
+                while let Some(lir::Statement::TableRow(_))
+                | Some(lir::Statement::TableSeparator(_))
+                | Some(lir::Statement::Comment(_)) = statements_lir.get(idx)
+                {
+                    idx += 1;
+                }
+
+                statements.push(hir::Statement::Table(parse_table(
+                    ctx,
+                    &statements_lir[start..idx],
+                )?));
+            }
+            lir::Statement::VariableDefinition(lir::VariableDefinitionStatement {
+                name,
+                value,
+            }) => {
+                ctx.variables.insert(name, value);
+                idx += 1;
+            }
+            lir::Statement::TableOfContents(lir::TableOfContentsStatement {
+                min_level,
+                max_level,
+            }) => {
+                // The list is filled in once all headers are known
+                statements.push(hir::Statement::TableOfContents(
+                    hir::TableOfContentsStatement {
+                        min_level: *min_level,
+                        max_level: *max_level,
+                        list: hir::List {
+                            list_type: hir::ListType::Unordered,
+                            items: vec![],
+                        },
+                    },
+                ));
+                idx += 1;
+            }
+            lir::Statement::Bibliography(lir::BibliographyStatement) => {
+                // The entries are filled in once all citations are known
+                statements.push(hir::Statement::Bibliography(hir::BibliographyStatement {
+                    style: ctx.citation_style,
+                    entries: vec![],
+                }));
+                idx += 1;
+            }
+            lir::Statement::Include(lir::IncludeStatement { path }) => {
+                return Err(Error::UnresolvedInclude(path.to_string()));
+            }
+            lir::Statement::AdmonitionStart(start_stmt) => {
+                // The title is parsed first to keep footnotes in order
+                let title = match &start_stmt.title {
+                    Some(title) => parse_text_segments(ctx, &title.segments)?,
+                    None => vec![],
+                };
+                containers.push((start_stmt, title, std::mem::take(&mut statements)));
+                idx += 1;
+            }
+            lir::Statement::AdmonitionEnd(_) => {
+                let (start_stmt, title, parent) =
+                    containers.pop().ok_or(Error::UnmatchedContainerEnd)?;
+                let body = std::mem::replace(&mut statements, parent);
+                statements.extend(parse_container(ctx, start_stmt, title, body)?);
+                idx += 1;
+            }
+            lir::Statement::Directive(lir::DirectiveStatement {
+                name,
+                label,
+                arguments,
+            }) => {
+                let directive = Directive {
+                    name,
+                    kind: DirectiveKind::Leaf,
+                    label: parse_text_segments(ctx, label)?,
+                    arguments: arguments.clone(),
+                    body: vec![],
+                };
+                statements.extend(expand_block_directive(ctx, directive)?);
+                idx += 1;
+            }
+            lir::Statement::BlockQuote(_) => {
+                let mut quoted = Vec::new();
+
+                loop {
+                    match statements_lir.get(idx) {
+                        Some(lir::Statement::BlockQuote(block_quote_stmt)) => {
+                            quoted.push((*block_quote_stmt.statement).clone())
+                        }
+                        Some(lir::Statement::Comment(_)) => (),
+                        _ => break,
+                    }
+                    idx += 1;
+                }
+
+                statements.push(hir::Statement::BlockQuote(hir::BlockQuoteStatement {
+                    statements: parse_statements(ctx, &quoted)?,
+                }));
+            }
+        }
+
+        if block_attributes.is_some() {
+            return Err(Error::DanglingAttributes);
         }
     }
 
-    Ok(hir::Document { statements })
+    if block_attributes.is_some() {
+        return Err(Error::DanglingAttributes);
+    }
+
+    if let Some((start_stmt, ..)) = containers.last() {
+        return Err(Error::UnclosedContainer(start_stmt.kind.to_string()));
+    }
+
+    Ok(statements)
 }
 
 #[derive(Debug)]
@@ -73,18 +705,485 @@ enum Either<L, R> {
 }
 
 fn parse_header_statement<'a>(
+    ctx: &mut Context<'a>,
     header_statement: &lir::HeaderStatement<'a>,
+    mut attributes: lir::Attributes<'a>,
 ) -> Result<hir::HeaderStatement<'a>> {
+    let segments = parse_text_segments(ctx, &header_statement.text.segments)?;
+    attributes.extend(&header_statement.attributes);
     Ok(hir::HeaderStatement {
         header_type: header_statement.header_type,
-        text: hir::Text {
-            segments: parse_text_segments(&header_statement.text.segments)?,
+        // Generated IDs are filled in once all explicit ones are known
+        id: attributes.id.take().map(str::to_string).unwrap_or_default(),
+        attributes: check_attributes(ctx, attributes)?,
+        text: hir::Text { segments },
+    })
+}
+
+/// Rejects keys repeated by merging attribute lists, as well as event
+/// handlers like `onclick` and inline styles when raw content is forbidden.
+fn check_attributes<'a>(
+    ctx: &Context<'a>,
+    attributes: lir::Attributes<'a>,
+) -> Result<hir::Attributes<'a>> {
+    for (idx, (key, _)) in attributes.pairs.iter().enumerate() {
+        if attributes.pairs[..idx]
+            .iter()
+            .any(|(key_, _)| key_.eq_ignore_ascii_case(key))
+        {
+            return Err(Error::DuplicateAttribute(key.to_string()));
+        }
+    }
+
+    if ctx.forbid_raw {
+        if let Some((key, _)) = attributes.pairs.iter().find(|(key, _)| {
+            let key = key.to_ascii_lowercase();
+            key.starts_with("on") || key == "style"
+        }) {
+            return Err(Error::ForbiddenAttribute(key.to_string()));
+        }
+    }
+    Ok(attributes)
+}
+
+/// Replaces `@if` blocks with the statements of their first branch whose
+/// condition holds, pairing the keywords up in a single pass. Blocks inside a
+/// block quote are resolved among the quoted lines.
+///
+/// Every condition is evaluated, so that unknown flags are reported even in
+/// branches that would not be included.
+fn resolve_conditionals<'a>(
+    statements: &[lir::Statement<'a>],
+    flags: &HashMap<String, String>,
+) -> Result<Vec<lir::Statement<'a>>> {
+    struct Block {
+        /// Whether the block itself is included.
+        included: bool,
+        /// Whether an earlier branch was taken.
+        taken: bool,
+        /// Whether the current branch is taken.
+        active: bool,
+        otherwise: bool,
+    }
+
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut resolved = Vec::with_capacity(statements.len());
+
+    let mut idx = 0;
+    while idx < statements.len() {
+        let active = match blocks.last() {
+            Some(block) => block.active,
+            None => true,
+        };
+        match &statements[idx] {
+            lir::Statement::Conditional(conditional) => {
+                let keyword = conditional_keyword(conditional);
+                match conditional {
+                    lir::ConditionalStatement::If(condition) => {
+                        let holds = evaluate_condition(condition, flags)?;
+                        blocks.push(Block {
+                            included: active,
+                            taken: holds,
+                            active: active && holds,
+                            otherwise: false,
+                        });
+                    }
+                    lir::ConditionalStatement::Elif(condition) => {
+                        let holds = evaluate_condition(condition, flags)?;
+                        let block = match blocks.last_mut() {
+                            Some(block) if !block.otherwise => block,
+                            _ => return Err(Error::UnmatchedConditional(keyword)),
+                        };
+                        block.active = block.included && !block.taken && holds;
+                        block.taken |= holds;
+                    }
+                    lir::ConditionalStatement::Else => {
+                        let block = match blocks.last_mut() {
+                            Some(block) if !block.otherwise => block,
+                            _ => return Err(Error::UnmatchedConditional(keyword)),
+                        };
+                        block.active = block.included && !block.taken;
+                        block.otherwise = true;
+                    }
+                    lir::ConditionalStatement::End => {
+                        blocks.pop().ok_or(Error::UnmatchedConditional(keyword))?;
+                    }
+                }
+                idx += 1;
+            }
+            lir::Statement::BlockQuote(_) => {
+                // Comments don't split a quote, just like in `parse_statements`
+                let mut quoted = Vec::new();
+                loop {
+                    match statements.get(idx) {
+                        Some(lir::Statement::BlockQuote(block_quote_stmt)) => {
+                            quoted.push((*block_quote_stmt.statement).clone())
+                        }
+                        Some(lir::Statement::Comment(_)) => (),
+                        _ => break,
+                    }
+                    idx += 1;
+                }
+
+                let quoted = resolve_conditionals(&quoted, flags)?;
+                if active {
+                    resolved.extend(quoted.into_iter().map(|statement| {
+                        lir::Statement::BlockQuote(lir::BlockQuoteStatement {
+                            statement: Box::new(statement),
+                        })
+                    }));
+                }
+            }
+            statement => {
+                if active {
+                    resolved.push(statement.clone());
+                }
+                idx += 1;
+            }
+        }
+    }
+
+    if !blocks.is_empty() {
+        return Err(Error::UnclosedConditional);
+    }
+
+    Ok(resolved)
+}
+
+fn conditional_keyword(conditional: &lir::ConditionalStatement<'_>) -> &'static str {
+    match conditional {
+        lir::ConditionalStatement::If(_) => "@if",
+        lir::ConditionalStatement::Elif(_) => "@elif",
+        lir::ConditionalStatement::Else => "@else",
+        lir::ConditionalStatement::End => "@end",
+    }
+}
+
+fn evaluate_condition(
+    condition: &lir::Condition<'_>,
+    flags: &HashMap<String, String>,
+) -> Result<bool> {
+    let flag = |name: &str| {
+        flags
+            .get(name)
+            .map(String::as_str)
+            .ok_or_else(|| Error::UnknownFlag(name.to_string()))
+    };
+
+    let holds = match condition {
+        lir::Condition::Flag(name) => !matches!(flag(name)?, "" | "false"),
+        lir::Condition::Equal(name, value) => flag(name)? == *value,
+        lir::Condition::NotEqual(name, value) => flag(name)? != *value,
+        lir::Condition::Not(condition) => !evaluate_condition(condition, flags)?,
+        // Both sides are evaluated to check their flags
+        lir::Condition::And(lhs, rhs) => {
+            let lhs = evaluate_condition(lhs, flags)?;
+            evaluate_condition(rhs, flags)? && lhs
+        }
+        lir::Condition::Or(lhs, rhs) => {
+            let lhs = evaluate_condition(lhs, flags)?;
+            evaluate_condition(rhs, flags)? || lhs
+        }
+    };
+
+    Ok(holds)
+}
+
+/// Appends a lowercase, dash-separated version of the plain text of `segments` to `slug`.
+fn slugify(segments: &[hir::TextSegment<'_>], slug: &mut String) {
+    fn push(text: &str, slug: &mut String) {
+        for c in text.chars() {
+            if c.is_alphanumeric() {
+                slug.extend(c.to_lowercase());
+            } else if (c.is_whitespace() || c == '-' || c == '_')
+                && !slug.is_empty()
+                && !slug.ends_with('-')
+            {
+                slug.push('-');
+            }
+        }
+    }
+
+    for segment in segments {
+        match segment {
+            hir::TextSegment::Text(text) => push(text, slug),
+            hir::TextSegment::Code(text) | hir::TextSegment::Math(text) => push(text, slug),
+            hir::TextSegment::Break => push(" ", slug),
+            hir::TextSegment::Emphasised { inner, .. } => slugify(inner, slug),
+            hir::TextSegment::Link { label, .. } => slugify(label, slug),
+            hir::TextSegment::Span { inner, .. } => slugify(inner, slug),
+            hir::TextSegment::CrossReference { text, .. } => slugify(text, slug),
+            hir::TextSegment::Image(image) => push(image.alt, slug),
+            hir::TextSegment::FootnoteReference { .. }
+            | hir::TextSegment::Citation { .. }
+            | hir::TextSegment::Raw { .. } => (),
+        }
+    }
+
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+}
+
+/// Collects the spans with an ID within `segments`.
+fn collect_span_targets<'a>(segments: &[hir::TextSegment<'a>], targets: &mut Vec<Target<'a>>) {
+    for segment in segments {
+        match segment {
+            hir::TextSegment::Span { inner, attributes } => {
+                if let Some(id) = attributes.id {
+                    targets.push(Target {
+                        header_type: None,
+                        id: id.to_string(),
+                        label: target_label(inner),
+                    });
+                }
+                collect_span_targets(inner, targets);
+            }
+            hir::TextSegment::Emphasised { inner, .. } => collect_span_targets(inner, targets),
+            hir::TextSegment::Link { label, .. } => collect_span_targets(label, targets),
+            _ => (),
+        }
+    }
+}
+
+/// Returns `segments` as the text of a link to their element: links are
+/// replaced by their label, span IDs are dropped and so are the footnote
+/// references and citations that belong to the element only.
+fn target_label<'a>(segments: &[hir::TextSegment<'a>]) -> Vec<hir::TextSegment<'a>> {
+    let mut label = Vec::new();
+
+    for segment in segments {
+        match segment {
+            hir::TextSegment::Link { label: inner, .. } => label.extend(target_label(inner)),
+            hir::TextSegment::Emphasised { emphasis, inner } => {
+                label.push(hir::TextSegment::Emphasised {
+                    emphasis: *emphasis,
+                    inner: target_label(inner),
+                })
+            }
+            hir::TextSegment::Span { inner, attributes } => label.push(hir::TextSegment::Span {
+                inner: target_label(inner),
+                attributes: hir::Attributes {
+                    id: None,
+                    ..attributes.clone()
+                },
+            }),
+            hir::TextSegment::FootnoteReference { .. } | hir::TextSegment::Citation { .. } => (),
+            segment => label.push(segment.clone()),
+        }
+    }
+
+    label
+}
+
+/// Replaces the cross-references within a label by the label of their target,
+/// dropping those within the latter so that references can't form a cycle.
+fn inline_cross_references<'a>(
+    segments: &[hir::TextSegment<'a>],
+    targets: Option<&[Target<'a>]>,
+) -> Vec<hir::TextSegment<'a>> {
+    let mut label = Vec::new();
+
+    for segment in segments {
+        match segment {
+            hir::TextSegment::CrossReference { id, .. } => {
+                if let Some(target) =
+                    targets.and_then(|targets| targets.iter().find(|target| target.id == *id))
+                {
+                    label.extend(inline_cross_references(&target.label, None));
+                }
+            }
+            hir::TextSegment::Emphasised { emphasis, inner } => {
+                label.push(hir::TextSegment::Emphasised {
+                    emphasis: *emphasis,
+                    inner: inline_cross_references(inner, targets),
+                })
+            }
+            hir::TextSegment::Span { inner, attributes } => label.push(hir::TextSegment::Span {
+                inner: inline_cross_references(inner, targets),
+                attributes: attributes.clone(),
+            }),
+            segment => label.push(segment.clone()),
+        }
+    }
+
+    label
+}
+
+fn resolve_cross_references<'a>(segments: &mut [hir::TextSegment<'a>], targets: &[Target<'a>]) {
+    for segment in segments {
+        match segment {
+            hir::TextSegment::CrossReference { id, text } => {
+                if let Some(target) = targets.iter().find(|target| target.id == *id) {
+                    *text = target.label.clone();
+                }
+            }
+            hir::TextSegment::Emphasised { inner, .. } => resolve_cross_references(inner, targets),
+            hir::TextSegment::Link { label, .. } => resolve_cross_references(label, targets),
+            hir::TextSegment::Span { inner, .. } => resolve_cross_references(inner, targets),
+            _ => (),
+        }
+    }
+}
+
+/// Builds a list nesting each header's deeper successors below it.
+fn toc_list<'a>(headers: &[&Target<'a>]) -> hir::List<'a> {
+    let mut items = Vec::new();
+
+    let mut idx = 0;
+    while idx < headers.len() {
+        let header = headers[idx];
+        idx += 1;
+
+        let start = idx;
+        while idx < headers.len() && headers[idx].header_type > header.header_type {
+            idx += 1;
+        }
+
+        items.push(hir::ListItem {
+            checked: None,
+            text: hir::Text {
+                segments: vec![hir::TextSegment::CrossReference {
+                    id: Cow::Owned(header.id.clone()),
+                    text: header.label.clone(),
+                }],
+            },
+            children: if start < idx {
+                vec![toc_list(&headers[start..idx])]
+            } else {
+                vec![]
+            },
+        });
+    }
+
+    hir::List {
+        list_type: hir::ListType::Unordered,
+        items,
+    }
+}
+
+/// Calls `f` with every statement, including nested ones.
+fn visit_statements<'a>(
+    statements: &mut [hir::Statement<'a>],
+    f: &mut dyn FnMut(&mut hir::Statement<'a>),
+) {
+    for statement in statements {
+        match statement {
+            hir::Statement::BlockQuote(hir::BlockQuoteStatement { statements })
+            | hir::Statement::Admonition(hir::AdmonitionStatement { statements, .. }) => {
+                visit_statements(statements, f)
+            }
+            _ => (),
+        }
+        f(statement);
+    }
+}
+
+/// Calls `f` with the segments of every text in `statements`, including nested ones.
+fn visit_texts<'a>(
+    statements: &mut [hir::Statement<'a>],
+    f: &mut dyn FnMut(&mut Vec<hir::TextSegment<'a>>),
+) {
+    for statement in statements {
+        match statement {
+            hir::Statement::Header(hir::HeaderStatement { text, .. })
+            | hir::Statement::Paragraph(hir::ParagraphStatement { text, .. }) => {
+                f(&mut text.segments)
+            }
+            hir::Statement::Figure(hir::FigureStatement { caption, .. }) => {
+                f(&mut caption.segments)
+            }
+            hir::Statement::List(hir::ListStatement { list, .. }) => visit_list_texts(list, f),
+            hir::Statement::Table(table) => {
+                for row in table.header.iter_mut().chain(table.rows.iter_mut()) {
+                    for cell in &mut row.cells {
+                        f(&mut cell.segments);
+                    }
+                }
+            }
+            hir::Statement::DefinitionList(hir::DefinitionListStatement { items, .. }) => {
+                for item in items {
+                    f(&mut item.term.segments);
+                    for definition in &mut item.definitions {
+                        f(&mut definition.segments);
+                    }
+                }
+            }
+            hir::Statement::Admonition(hir::AdmonitionStatement {
+                title, statements, ..
+            }) => {
+                if let Some(title) = title {
+                    f(&mut title.segments);
+                }
+                visit_texts(statements, f);
+            }
+            hir::Statement::BlockQuote(hir::BlockQuoteStatement { statements }) => {
+                visit_texts(statements, f)
+            }
+            hir::Statement::HorizontalRule(_)
+            | hir::Statement::TableOfContents(_)
+            | hir::Statement::Bibliography(_)
+            | hir::Statement::CodeBlock(_)
+            | hir::Statement::Raw(_)
+            | hir::Statement::MathBlock(_) => (),
+        }
+    }
+}
+
+fn visit_list_texts<'a>(
+    list: &mut hir::List<'a>,
+    f: &mut dyn FnMut(&mut Vec<hir::TextSegment<'a>>),
+) {
+    for item in &mut list.items {
+        f(&mut item.text.segments);
+        for child in &mut item.children {
+            visit_list_texts(child, f);
+        }
+    }
+}
+
+fn parse_figure_statement<'a>(
+    ctx: &mut Context<'a>,
+    figure_statement: &lir::FigureStatement<'a>,
+) -> Result<hir::FigureStatement<'a>> {
+    Ok(hir::FigureStatement {
+        image: parse_image(ctx, &figure_statement.image)?,
+        caption: hir::Text {
+            segments: parse_text_segments(ctx, &figure_statement.caption.segments)?,
         },
     })
 }
 
+fn parse_image<'a>(ctx: &Context<'a>, image: &lir::Image<'a>) -> Result<hir::Image<'a>> {
+    check_url(ctx, image.url)?;
+    Ok(hir::Image {
+        alt: image.alt,
+        url: image.url,
+        title: image.title,
+    })
+}
+
+/// Rejects `javascript:` and `vbscript:` URLs when raw content is forbidden.
+fn check_url(ctx: &Context<'_>, url: &str) -> Result<()> {
+    if ctx.forbid_raw {
+        // Browsers ignore whitespace and control characters within the scheme
+        let scheme = url
+            .chars()
+            .take_while(|c| *c != ':')
+            .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if url.contains(':') && (scheme == "javascript" || scheme == "vbscript") {
+            return Err(Error::ForbiddenUrl(url.to_string()));
+        }
+    }
+    Ok(())
+}
+
 fn parse_paragraph_statement<'a>(
+    ctx: &mut Context<'a>,
     paragraphs: &[&lir::ParagraphStatement<'a>],
+    attributes: lir::Attributes<'a>,
 ) -> Result<hir::ParagraphStatement<'a>> {
     // Collect into one vec
     let segments = {
@@ -101,23 +1200,248 @@ fn parse_paragraph_statement<'a>(
     };
 
     Ok(hir::ParagraphStatement {
+        attributes: check_attributes(ctx, attributes)?,
         text: hir::Text {
-            segments: parse_text_segments(&segments)?,
+            segments: parse_text_segments(ctx, &segments)?,
         },
     })
 }
 
-fn parse_list<'a>(
+fn admonition_kind(kind: &str) -> Option<hir::AdmonitionKind> {
+    match kind {
+        "note" => Some(hir::AdmonitionKind::Note),
+        "tip" => Some(hir::AdmonitionKind::Tip),
+        "warning" => Some(hir::AdmonitionKind::Warning),
+        "danger" => Some(hir::AdmonitionKind::Danger),
+        _ => None,
+    }
+}
+
+/// Builds the admonition or custom container directive opened by `start_stmt`.
+fn parse_container<'a>(
+    ctx: &mut Context<'a>,
+    start_stmt: &lir::AdmonitionStartStatement<'a>,
+    title: Vec<hir::TextSegment<'a>>,
+    body: Vec<hir::Statement<'a>>,
+) -> Result<Vec<hir::Statement<'a>>> {
+    match admonition_kind(start_stmt.kind) {
+        Some(kind) => Ok(vec![hir::Statement::Admonition(hir::AdmonitionStatement {
+            kind,
+            attributes: check_attributes(ctx, start_stmt.arguments.clone())?,
+            title: if title.is_empty() {
+                None
+            } else {
+                Some(hir::Text { segments: title })
+            },
+            statements: body,
+        })]),
+        None => {
+            let directive = Directive {
+                name: start_stmt.kind,
+                kind: DirectiveKind::Container,
+                label: title,
+                arguments: start_stmt.arguments.clone(),
+                body,
+            };
+            expand_block_directive(ctx, directive)
+        }
+    }
+}
+
+fn expand_block_directive<'a>(
+    ctx: &Context<'a>,
+    directive: Directive<'a>,
+) -> Result<Vec<hir::Statement<'a>>> {
+    let name = directive.name;
+    let handler = ctx
+        .directives
+        .block(name)
+        .ok_or_else(|| Error::UnregisteredDirective(name.to_string()))?;
+
+    handler(directive).map_err(|message| Error::Directive {
+        name: name.to_string(),
+        message,
+    })
+}
+
+fn expand_inline_directive<'a>(
+    ctx: &Context<'a>,
+    directive: Directive<'a>,
+) -> Result<Vec<hir::TextSegment<'a>>> {
+    let name = directive.name;
+    let handler = ctx
+        .directives
+        .inline(name)
+        .ok_or_else(|| Error::UnregisteredDirective(name.to_string()))?;
+
+    handler(directive).map_err(|message| Error::Directive {
+        name: name.to_string(),
+        message,
+    })
+}
+
+/// Returns the end of the definition list whose first definition is at `idx`.
+fn definition_list_end(statements_lir: &[lir::Statement<'_>], mut idx: usize) -> usize {
+    let skip_comments = |mut idx: usize| {
+        while let Some(lir::Statement::Comment(_)) = statements_lir.get(idx) {
+            idx += 1;
+        }
+        idx
+    };
+
+    loop {
+        while let Some(lir::Statement::Definition(_)) = statements_lir.get(skip_comments(idx)) {
+            idx = skip_comments(idx) + 1;
+        }
+
+        // Further terms may follow after empty lines
+        let mut next = idx;
+        while let Some(lir::Statement::EmptyLine(_)) | Some(lir::Statement::Comment(_)) =
+            statements_lir.get(next)
+        {
+            next += 1;
+        }
+
+        match statements_lir.get(next) {
+            Some(lir::Statement::Paragraph(_)) => {
+                let definition = skip_comments(next + 1);
+                match statements_lir.get(definition) {
+                    Some(lir::Statement::Definition(_)) => idx = definition,
+                    _ => return idx,
+                }
+            }
+            _ => return idx,
+        }
+    }
+}
+
+fn parse_definition_list<'a>(
+    ctx: &mut Context<'a>,
+    statements: &[lir::Statement<'a>],
+    attributes: lir::Attributes<'a>,
+) -> Result<hir::DefinitionListStatement<'a>> {
+    let mut items: Vec<hir::DefinitionItem<'a>> = Vec::new();
+
+    for statement in statements {
+        match statement {
+            lir::Statement::Paragraph(lir::ParagraphStatement { text }) => {
+                items.push(hir::DefinitionItem {
+                    term: hir::Text {
+                        segments: parse_text_segments(ctx, &text.segments)?,
+                    },
+                    definitions: Vec::new(),
+                });
+            }
+            lir::Statement::Definition(lir::DefinitionStatement { text }) => {
+                let definition = hir::Text {
+                    segments: parse_text_segments(ctx, &text.segments)?,
+                };
+                items
+                    .last_mut()
+                    .ok_or(Error::DefinitionWithoutTerm)?
+                    .definitions
+                    .push(definition);
+            }
+            _ => (),
+        }
+    }
+
+    Ok(hir::DefinitionListStatement {
+        attributes: check_attributes(ctx, attributes)?,
+        items,
+    })
+}
+
+fn parse_table<'a>(
+    ctx: &mut Context<'a>,
+    statements: &[lir::Statement<'a>],
+) -> Result<hir::TableStatement<'a>> {
+    let statements = statements
+        .iter()
+        .filter(|statement| !matches!(statement, lir::Statement::Comment(_)))
+        .collect::<Vec<_>>();
+
+    // A separator directly below the first row turns it into the header
+    let (header, alignments, body) = match statements.as_slice() {
+        [lir::Statement::TableRow(header), lir::Statement::TableSeparator(separator), body @ ..] => {
+            (
+                Some(parse_table_row(ctx, header)?),
+                separator.alignments.clone(),
+                body,
+            )
+        }
+        body => (None, Vec::new(), body),
+    };
+
+    let mut rows = Vec::new();
+    for statement in body {
+        match statement {
+            lir::Statement::TableRow(row) => rows.push(parse_table_row(ctx, row)?),
+            _ => return Err(Error::MisplacedTableSeparator),
+        }
+    }
+
+    Ok(hir::TableStatement {
+        alignments,
+        header,
+        rows,
+    })
+}
+
+fn parse_table_row<'a>(
+    ctx: &mut Context<'a>,
+    row: &lir::TableRowStatement<'a>,
+) -> Result<hir::TableRow<'a>> {
+    let mut cells = Vec::new();
+    for cell in &row.cells {
+        cells.push(hir::Text {
+            segments: parse_text_segments(ctx, &cell.segments)?,
+        });
+    }
+
+    Ok(hir::TableRow { cells })
+}
+
+/// Parses list items into lists, starting a new list wherever a sibling
+/// switches between bullets and numbers.
+fn parse_lists<'a>(
+    ctx: &mut Context<'a>,
     statements: &[Either<&lir::ListItemStatement<'a>, &lir::ParagraphStatement<'a>>],
-) -> Result<hir::List<'a>> {
-    let mut items = vec![];
+) -> Result<Vec<hir::List<'a>>> {
+    let mut lists: Vec<hir::List<'a>> = vec![];
 
     let mut idx = 0;
     while idx < statements.len() {
         match statements[idx] {
-            Either::Left(lir::ListItemStatement { indentation, text }) => {
+            Either::Left(lir::ListItemStatement {
+                indentation,
+                marker,
+                checked,
+                text,
+            }) => {
                 idx += 1;
 
+                let list_type = match marker {
+                    lir::ListMarker::Number(start) => hir::ListType::Ordered { start: *start },
+                    lir::ListMarker::Bullet => hir::ListType::Unordered,
+                };
+                let continues_list = match lists.last() {
+                    Some(list) => {
+                        matches!(
+                            (list.list_type, list_type),
+                            (hir::ListType::Unordered, hir::ListType::Unordered)
+                                | (hir::ListType::Ordered { .. }, hir::ListType::Ordered { .. })
+                        )
+                    }
+                    None => false,
+                };
+                if !continues_list {
+                    lists.push(hir::List {
+                        list_type,
+                        items: vec![],
+                    });
+                }
+
                 let mut text_segments = text.segments.clone();
 
                 while let Some(Either::Right(lir::ParagraphStatement { text })) =
@@ -128,9 +1452,9 @@ fn parse_list<'a>(
                     idx += 1;
                 }
 
-                let mut text_segments = parse_text_segments(&text_segments)?;
+                let mut text_segments = parse_text_segments(ctx, &text_segments)?;
 
-                let child = match statements.get(idx) {
+                let children = match statements.get(idx) {
                     Some(Either::Left(list_item_stmt))
                         if list_item_stmt.indentation >= indentation + 2 =>
                     {
@@ -149,59 +1473,130 @@ fn parse_list<'a>(
                             idx += 1;
                         }
 
-                        Some(parse_list(&statements[start..idx])?)
+                        parse_lists(ctx, &statements[start..idx])?
                     }
-                    _ => None,
+                    _ => vec![],
                 };
 
-                if text_segments.is_empty() && child.is_some() {
+                if text_segments.is_empty() && !children.is_empty() {
                     text_segments.push(hir::TextSegment::Break);
                 }
 
-                items.push(hir::ListItem {
-                    text: hir::Text {
-                        segments: text_segments,
-                    },
-                    child,
-                });
+                if let Some(list) = lists.last_mut() {
+                    list.items.push(hir::ListItem {
+                        checked: *checked,
+                        text: hir::Text {
+                            segments: text_segments,
+                        },
+                        children,
+                    });
+                }
             }
             Either::Right(lir::ParagraphStatement { .. }) => unreachable!(),
         }
     }
 
-    Ok(hir::List { items })
+    Ok(lists)
 }
 
 fn parse_text_segments<'a>(
+    ctx: &mut Context<'a>,
     segments_lir: &[lir::TextSegment<'a>],
 ) -> Result<Vec<hir::TextSegment<'a>>> {
     let mut segments = Vec::new();
 
     let mut idx = 0;
     while idx < segments_lir.len() {
-        match segments_lir[idx] {
+        match &segments_lir[idx] {
             lir::TextSegment::Text(text) => {
-                segments.push(hir::TextSegment::Text(text));
+                segments.push(hir::TextSegment::Text(text.clone()));
+                idx += 1;
+            }
+            lir::TextSegment::Link { label, url, title } => {
+                check_url(ctx, url)?;
+                segments.push(hir::TextSegment::Link {
+                    label: parse_text_segments(ctx, label)?,
+                    url,
+                    title: *title,
+                });
+                idx += 1;
+            }
+            lir::TextSegment::Image(image) => {
+                segments.push(hir::TextSegment::Image(parse_image(ctx, image)?));
+                idx += 1;
+            }
+            lir::TextSegment::FootnoteReference(label) => {
+                segments.push(ctx.reference_footnote(label));
+                idx += 1;
+            }
+            lir::TextSegment::Span { text, attributes } => {
+                segments.push(hir::TextSegment::Span {
+                    inner: parse_text_segments(ctx, text)?,
+                    attributes: check_attributes(ctx, attributes.clone())?,
+                });
+                idx += 1;
+            }
+            lir::TextSegment::Directive {
+                name,
+                label,
+                arguments,
+            } => {
+                let directive = Directive {
+                    name,
+                    kind: DirectiveKind::Inline,
+                    label: parse_text_segments(ctx, label)?,
+                    arguments: arguments.clone(),
+                    body: vec![],
+                };
+                segments.extend(expand_inline_directive(ctx, directive)?);
+                idx += 1;
+            }
+            lir::TextSegment::Citation(citations) => {
+                let items = citations
+                    .iter()
+                    .map(|citation| ctx.cite(citation))
+                    .collect::<Result<_>>()?;
+                segments.push(hir::TextSegment::Citation {
+                    style: ctx.citation_style,
+                    items,
+                });
+                idx += 1;
+            }
+            lir::TextSegment::CrossReference(id) => {
+                ctx.cross_references.push(id);
+                segments.push(hir::TextSegment::CrossReference {
+                    id: Cow::Borrowed(id),
+                    text: vec![],
+                });
+                idx += 1;
+            }
+            lir::TextSegment::Variable(name) => {
+                segments.push(hir::TextSegment::Text(ctx.variable(name)?));
+                idx += 1;
+            }
+            lir::TextSegment::Math(math) => {
+                segments.push(hir::TextSegment::Math(math));
+                idx += 1;
+            }
+            lir::TextSegment::Code(code) => {
+                segments.push(hir::TextSegment::Code(code));
                 idx += 1;
             }
             lir::TextSegment::Break => {
                 segments.push(hir::TextSegment::Break);
                 idx += 1;
             }
-            lir::TextSegment::Emphasis(emph) => {
-                let offset =
-                    &segments_lir[idx + 1..]
-                        .into_iter()
-                        .position(|segment| match segment {
-                            lir::TextSegment::Emphasis(emph_end) => emph == *emph_end,
-                            _ => false,
-                        });
+            &lir::TextSegment::Emphasis(emph) => {
+                let offset = find_emphasis_end(emph, &segments_lir[idx + 1..]);
 
                 match offset {
                     Some(offset) => {
                         segments.push(hir::TextSegment::Emphasised {
                             emphasis: emph,
-                            inner: parse_text_segments(&segments_lir[idx + 1..idx + 1 + offset])?,
+                            inner: parse_text_segments(
+                                ctx,
+                                &segments_lir[idx + 1..idx + 1 + offset],
+                            )?,
                         });
                         idx += offset + 2;
                     }
@@ -210,8 +1605,12 @@ fn parse_text_segments<'a>(
                             lir::Emphasis::Bold => "**",
                             lir::Emphasis::Italic => "__",
                             lir::Emphasis::Strikethrough => "~~",
+                            lir::Emphasis::Underline => "++",
+                            lir::Emphasis::Mark => "==",
+                            lir::Emphasis::Superscript => "^",
+                            lir::Emphasis::Subscript => "~",
                         };
-                        segments.push(hir::TextSegment::Text(text));
+                        segments.push(hir::TextSegment::Text(text.into()));
                         idx += 1;
                     }
                 }
@@ -222,6 +1621,65 @@ fn parse_text_segments<'a>(
     Ok(segments)
 }
 
+/// Finds the marker closing `emph` within the segments following its opener.
+///
+/// Underline, mark, superscript and subscript only pair up when the opener is
+/// followed and the closer is preceded by something other than whitespace, so
+/// `C++`, `~5` or `a == b` stay literal in prose. Superscripts and subscripts
+/// may not contain whitespace at all.
+fn find_emphasis_end(emph: lir::Emphasis, segments: &[lir::TextSegment<'_>]) -> Option<usize> {
+    let is_end = |segment: &lir::TextSegment<'_>| match segment {
+        lir::TextSegment::Emphasis(emph_end) => emph == *emph_end,
+        _ => false,
+    };
+
+    match emph {
+        lir::Emphasis::Bold | lir::Emphasis::Italic | lir::Emphasis::Strikethrough => {
+            return segments.iter().position(is_end);
+        }
+        lir::Emphasis::Underline
+        | lir::Emphasis::Mark
+        | lir::Emphasis::Superscript
+        | lir::Emphasis::Subscript => (),
+    }
+
+    if starts_with_whitespace(segments.first()) {
+        return None;
+    }
+
+    let offset = segments.iter().enumerate().position(|(offset, segment)| {
+        offset > 0 && is_end(segment) && !ends_with_whitespace(segments.get(offset - 1))
+    })?;
+
+    let contains_whitespace = segments[..offset].iter().any(|segment| match segment {
+        lir::TextSegment::Text(text) => text.contains(char::is_whitespace),
+        lir::TextSegment::Break => true,
+        _ => false,
+    });
+    match emph {
+        lir::Emphasis::Superscript | lir::Emphasis::Subscript if contains_whitespace => None,
+        _ => Some(offset),
+    }
+}
+
+/// Tells whether `segment` is missing, a break, or text starting with whitespace.
+fn starts_with_whitespace(segment: Option<&lir::TextSegment<'_>>) -> bool {
+    match segment {
+        None | Some(lir::TextSegment::Break) => true,
+        Some(lir::TextSegment::Text(text)) => text.starts_with(char::is_whitespace),
+        Some(_) => false,
+    }
+}
+
+/// Tells whether `segment` is missing, a break, or text ending with whitespace.
+fn ends_with_whitespace(segment: Option<&lir::TextSegment<'_>>) -> bool {
+    match segment {
+        None | Some(lir::TextSegment::Break) => true,
+        Some(lir::TextSegment::Text(text)) => text.ends_with(char::is_whitespace),
+        Some(_) => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -229,57 +1687,350 @@ mod tests {
     #[test]
     fn headers() {
         let doc_lir = lir::Document {
+            metadata: Default::default(),
             statements: vec![
                 lir::Statement::Header(lir::HeaderStatement {
                     header_type: lir::HeaderType::H1,
+                    attributes: lir::Attributes::default(),
                     text: lir::Text {
                         segments: vec![
-                            lir::TextSegment::Text("Hello "),
+                            lir::TextSegment::Text("Hello ".into()),
                             lir::TextSegment::Emphasis(lir::Emphasis::Italic),
-                            lir::TextSegment::Text("World"),
+                            lir::TextSegment::Text("World".into()),
                             lir::TextSegment::Emphasis(lir::Emphasis::Italic),
-                            lir::TextSegment::Text("!"),
+                            lir::TextSegment::Text("!".into()),
                         ],
                     },
                 }),
                 lir::Statement::Header(lir::HeaderStatement {
                     header_type: lir::HeaderType::H3,
+                    attributes: lir::Attributes::default(),
                     text: lir::Text {
                         segments: vec![
                             lir::TextSegment::Emphasis(lir::Emphasis::Strikethrough),
-                            lir::TextSegment::Text("Subtitle"),
+                            lir::TextSegment::Text("Subtitle".into()),
                         ],
                     },
                 }),
             ],
         };
         let doc_hir = hir::Document {
+            metadata: Default::default(),
+            footnotes: vec![],
             statements: vec![
                 hir::Statement::Header(hir::HeaderStatement {
                     header_type: hir::HeaderType::H1,
+                    id: "hello-world".to_string(),
+                    attributes: hir::Attributes::default(),
                     text: hir::Text {
                         segments: vec![
-                            hir::TextSegment::Text("Hello "),
+                            hir::TextSegment::Text("Hello ".into()),
                             hir::TextSegment::Emphasised {
                                 emphasis: hir::Emphasis::Italic,
-                                inner: vec![hir::TextSegment::Text("World")],
+                                inner: vec![hir::TextSegment::Text("World".into())],
                             },
-                            hir::TextSegment::Text("!"),
+                            hir::TextSegment::Text("!".into()),
                         ],
                     },
                 }),
                 hir::Statement::Header(hir::HeaderStatement {
                     header_type: hir::HeaderType::H3,
+                    id: "subtitle".to_string(),
+                    attributes: hir::Attributes::default(),
                     text: hir::Text {
                         segments: vec![
-                            hir::TextSegment::Text("~~"),
-                            hir::TextSegment::Text("Subtitle"),
+                            hir::TextSegment::Text("~~".into()),
+                            hir::TextSegment::Text("Subtitle".into()),
                         ],
                     },
                 }),
             ],
         };
 
-        assert_eq!(parse(&doc_lir).unwrap(), doc_hir);
+        assert_eq!(parse(&doc_lir, &Options::default()).unwrap(), doc_hir);
+    }
+
+    #[test]
+    fn variables() {
+        let doc_lir = lir::Document {
+            metadata: Default::default(),
+            statements: vec![
+                lir::Statement::VariableDefinition(lir::VariableDefinitionStatement {
+                    name: "product",
+                    value: "Writer",
+                }),
+                lir::Statement::VariableDefinition(lir::VariableDefinitionStatement {
+                    name: "version",
+                    value: "1.0",
+                }),
+                lir::Statement::Paragraph(lir::ParagraphStatement {
+                    text: lir::Text {
+                        segments: vec![
+                            lir::TextSegment::Variable("product"),
+                            lir::TextSegment::Text(" ".into()),
+                            lir::TextSegment::Variable("version"),
+                        ],
+                    },
+                }),
+            ],
+        };
+        let mut options = Options::default();
+        options
+            .variables
+            .insert("version".to_string(), "2.0".to_string());
+        let doc_hir = hir::Document {
+            metadata: Default::default(),
+            footnotes: vec![],
+            statements: vec![hir::Statement::Paragraph(hir::ParagraphStatement {
+                attributes: hir::Attributes::default(),
+                text: hir::Text {
+                    segments: vec![
+                        hir::TextSegment::Text("Writer".into()),
+                        hir::TextSegment::Text(" ".into()),
+                        hir::TextSegment::Text("2.0".into()),
+                    ],
+                },
+            })],
+        };
+
+        assert_eq!(parse(&doc_lir, &options).unwrap(), doc_hir);
+
+        // Variables are only defined after their definition
+        let doc_lir = lir::Document {
+            metadata: Default::default(),
+            statements: doc_lir.statements.into_iter().rev().collect(),
+        };
+        assert!(matches!(
+            parse(&doc_lir, &options),
+            Err(Error::UndefinedVariable(name)) if name == "product"
+        ));
+    }
+
+    #[test]
+    fn definition_lists() {
+        let lir_text = |text| lir::Text {
+            segments: vec![lir::TextSegment::Text(Cow::Borrowed(text))],
+        };
+        let hir_text = |text| hir::Text {
+            segments: vec![hir::TextSegment::Text(Cow::Borrowed(text))],
+        };
+        let paragraph = |text| {
+            lir::Statement::Paragraph(lir::ParagraphStatement {
+                text: lir_text(text),
+            })
+        };
+        let definition = |text| {
+            lir::Statement::Definition(lir::DefinitionStatement {
+                text: lir_text(text),
+            })
+        };
+
+        let doc_lir = lir::Document {
+            metadata: Default::default(),
+            statements: vec![
+                paragraph("intro"),
+                paragraph("apple"),
+                definition("a fruit"),
+                definition("a company"),
+                lir::Statement::EmptyLine(lir::EmptyLineStatement),
+                paragraph("pear"),
+                definition("another fruit"),
+                lir::Statement::EmptyLine(lir::EmptyLineStatement),
+                paragraph("outro"),
+            ],
+        };
+        let doc_hir = hir::Document {
+            metadata: Default::default(),
+            footnotes: vec![],
+            statements: vec![
+                hir::Statement::Paragraph(hir::ParagraphStatement {
+                    attributes: hir::Attributes::default(),
+                    text: hir_text("intro"),
+                }),
+                hir::Statement::DefinitionList(hir::DefinitionListStatement {
+                    attributes: hir::Attributes::default(),
+                    items: vec![
+                        hir::DefinitionItem {
+                            term: hir_text("apple"),
+                            definitions: vec![hir_text("a fruit"), hir_text("a company")],
+                        },
+                        hir::DefinitionItem {
+                            term: hir_text("pear"),
+                            definitions: vec![hir_text("another fruit")],
+                        },
+                    ],
+                }),
+                hir::Statement::Paragraph(hir::ParagraphStatement {
+                    attributes: hir::Attributes::default(),
+                    text: hir_text("outro"),
+                }),
+            ],
+        };
+
+        assert_eq!(parse(&doc_lir, &Options::default()).unwrap(), doc_hir);
+    }
+
+    #[test]
+    fn block_quotes() {
+        let quote = |statement| {
+            lir::Statement::BlockQuote(lir::BlockQuoteStatement {
+                statement: Box::new(statement),
+            })
+        };
+        let paragraph = |text| {
+            lir::Statement::Paragraph(lir::ParagraphStatement {
+                text: lir::Text {
+                    segments: vec![lir::TextSegment::Text(Cow::Borrowed(text))],
+                },
+            })
+        };
+
+        let doc_lir = lir::Document {
+            metadata: Default::default(),
+            statements: vec![
+                quote(paragraph("a")),
+                quote(paragraph("b")),
+                quote(lir::Statement::EmptyLine(lir::EmptyLineStatement)),
+                quote(quote(paragraph("c"))),
+                paragraph("d"),
+            ],
+        };
+        let doc_hir = hir::Document {
+            metadata: Default::default(),
+            footnotes: vec![],
+            statements: vec![
+                hir::Statement::BlockQuote(hir::BlockQuoteStatement {
+                    statements: vec![
+                        hir::Statement::Paragraph(hir::ParagraphStatement {
+                            attributes: hir::Attributes::default(),
+                            text: hir::Text {
+                                segments: vec![
+                                    hir::TextSegment::Text("a".into()),
+                                    hir::TextSegment::Break,
+                                    hir::TextSegment::Text("b".into()),
+                                ],
+                            },
+                        }),
+                        hir::Statement::BlockQuote(hir::BlockQuoteStatement {
+                            statements: vec![hir::Statement::Paragraph(hir::ParagraphStatement {
+                                attributes: hir::Attributes::default(),
+                                text: hir::Text {
+                                    segments: vec![hir::TextSegment::Text("c".into())],
+                                },
+                            })],
+                        }),
+                    ],
+                }),
+                hir::Statement::Paragraph(hir::ParagraphStatement {
+                    attributes: hir::Attributes::default(),
+                    text: hir::Text {
+                        segments: vec![hir::TextSegment::Text("d".into())],
+                    },
+                }),
+            ],
+        };
+
+        assert_eq!(parse(&doc_lir, &Options::default()).unwrap(), doc_hir);
+    }
+
+    #[test]
+    fn footnotes() {
+        let paragraph = |segments| {
+            lir::Statement::Paragraph(lir::ParagraphStatement {
+                text: lir::Text { segments },
+            })
+        };
+        let definition = |label, text| {
+            lir::Statement::FootnoteDefinition(lir::FootnoteDefinitionStatement {
+                label,
+                text: lir::Text {
+                    segments: vec![lir::TextSegment::Text(Cow::Borrowed(text))],
+                },
+            })
+        };
+
+        let doc_lir = lir::Document {
+            metadata: Default::default(),
+            statements: vec![
+                definition("a", "first"),
+                lir::Statement::EmptyLine(lir::EmptyLineStatement),
+                paragraph(vec![
+                    lir::TextSegment::FootnoteReference("b"),
+                    lir::TextSegment::FootnoteReference("a"),
+                    lir::TextSegment::FootnoteReference("b"),
+                ]),
+                definition("b", "second"),
+            ],
+        };
+        let doc_hir = hir::Document {
+            metadata: Default::default(),
+            footnotes: vec![
+                hir::Footnote {
+                    label: "b",
+                    text: hir::Text {
+                        segments: vec![hir::TextSegment::Text("second".into())],
+                    },
+                    references: 2,
+                },
+                hir::Footnote {
+                    label: "a",
+                    text: hir::Text {
+                        segments: vec![hir::TextSegment::Text("first".into())],
+                    },
+                    references: 1,
+                },
+            ],
+            statements: vec![hir::Statement::Paragraph(hir::ParagraphStatement {
+                attributes: hir::Attributes::default(),
+                text: hir::Text {
+                    segments: vec![
+                        hir::TextSegment::FootnoteReference {
+                            number: 1,
+                            occurrence: 1,
+                        },
+                        hir::TextSegment::FootnoteReference {
+                            number: 2,
+                            occurrence: 1,
+                        },
+                        hir::TextSegment::FootnoteReference {
+                            number: 1,
+                            occurrence: 2,
+                        },
+                    ],
+                },
+            })],
+        };
+        assert_eq!(parse(&doc_lir, &Options::default()).unwrap(), doc_hir);
+
+        let doc_lir = lir::Document {
+            metadata: Default::default(),
+            statements: vec![paragraph(vec![lir::TextSegment::FootnoteReference("x")])],
+        };
+        assert!(matches!(
+            parse(&doc_lir, &Options::default()),
+            Err(Error::UndefinedFootnote(label)) if label == "x"
+        ));
+
+        let doc_lir = lir::Document {
+            metadata: Default::default(),
+            statements: vec![definition("x", "unused"), definition("y", "unused too")],
+        };
+        assert!(matches!(
+            parse(&doc_lir, &Options::default()),
+            Err(Error::UnusedFootnote(label)) if label == "x"
+        ));
+
+        let doc_lir = lir::Document {
+            metadata: Default::default(),
+            statements: vec![
+                paragraph(vec![lir::TextSegment::FootnoteReference("x")]),
+                definition("x", "one"),
+                definition("x", "two"),
+            ],
+        };
+        assert!(matches!(
+            parse(&doc_lir, &Options::default()),
+            Err(Error::DuplicateFootnoteDefinition(label)) if label == "x"
+        ));
     }
 }
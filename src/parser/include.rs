@@ -0,0 +1,339 @@
+use crate::{ast::lir, parser};
+use std::{
+    collections::HashMap,
+    fmt, io,
+    path::{Component, Path, PathBuf},
+};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("cannot load {}", Chain(.chain))]
+    Load {
+        /// Files from the root up to the one that failed to load.
+        chain: Vec<PathBuf>,
+        source: io::Error,
+    },
+    #[error("cannot parse {}", Chain(.chain))]
+    Parse {
+        chain: Vec<PathBuf>,
+        source: parser::lir::Error,
+    },
+    #[error("include cycle {}", Chain(.0))]
+    Cycle(Vec<PathBuf>),
+}
+
+/// Formats an include chain as `a.w4 -> b.w4 -> c.w4`.
+struct Chain<'a>(&'a [PathBuf]);
+
+impl fmt::Display for Chain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, path) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" -> ")?;
+            }
+            write!(f, "`{}`", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// Provides the contents of included files.
+pub trait FileLoader {
+    fn load(&self, path: &Path) -> io::Result<String>;
+}
+
+/// Loads files from the file system.
+#[derive(Debug, Default, Clone)]
+pub struct FsLoader {
+    root: Option<PathBuf>,
+}
+
+impl FsLoader {
+    /// Creates a loader that rejects files outside of the `root` directory,
+    /// e.g. includes like `../../etc/passwd`.
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        FsLoader {
+            root: Some(root.into()),
+        }
+    }
+}
+
+impl FileLoader for FsLoader {
+    fn load(&self, path: &Path) -> io::Result<String> {
+        if let Some(root) = &self.root {
+            // Canonical paths have symbolic links resolved as well
+            if !path.canonicalize()?.starts_with(root.canonicalize()?) {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "file is outside of the root directory",
+                ));
+            }
+        }
+        std::fs::read_to_string(path)
+    }
+}
+
+impl FileLoader for HashMap<PathBuf, String> {
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such file"))
+    }
+}
+
+/// Contents of a root file and all files it transitively includes.
+///
+/// Loading happens up front so that the documents parsed afterwards can
+/// borrow from the sources.
+#[derive(Debug, Default)]
+pub struct Sources {
+    files: HashMap<PathBuf, String>,
+}
+
+impl Sources {
+    pub fn load(root: &Path, loader: &dyn FileLoader) -> Result<Self> {
+        let mut sources = Sources::default();
+        sources.load_file(normalize(root), &mut Vec::new(), loader)?;
+        Ok(sources)
+    }
+
+    fn load_file(
+        &mut self,
+        path: PathBuf,
+        chain: &mut Vec<PathBuf>,
+        loader: &dyn FileLoader,
+    ) -> Result<()> {
+        if chain.contains(&path) {
+            chain.push(path);
+            return Err(Error::Cycle(chain.clone()));
+        }
+        chain.push(path.clone());
+
+        if !self.files.contains_key(&path) {
+            let source = loader.load(&path).map_err(|source| Error::Load {
+                chain: chain.clone(),
+                source,
+            })?;
+            self.files.insert(path.clone(), source);
+        }
+
+        let mut includes = Vec::new();
+        collect_includes(&self.parse_file(&path, chain)?.statements, &mut includes);
+        let includes = includes
+            .into_iter()
+            .map(|include| resolve(&path, include))
+            .collect::<Vec<_>>();
+
+        for include in includes {
+            self.load_file(include, chain, loader)?;
+        }
+
+        chain.pop();
+        Ok(())
+    }
+
+    /// Parses the root file, replacing its `@include` statements with the
+    /// statements of the included files.
+    ///
+    /// Only the metadata of the root file is kept.
+    pub fn parse(&self, root: &Path) -> Result<lir::Document<'_>> {
+        self.parse_spliced(&normalize(root), &mut Vec::new())
+    }
+
+    fn parse_spliced(&self, path: &Path, chain: &mut Vec<PathBuf>) -> Result<lir::Document<'_>> {
+        chain.push(path.to_path_buf());
+
+        let document = self.parse_file(path, chain)?;
+        let statements = self.splice(path, document.statements, chain)?;
+
+        chain.pop();
+        Ok(lir::Document {
+            metadata: document.metadata,
+            statements,
+        })
+    }
+
+    /// Replaces includes in `statements` with the statements of the included
+    /// files.
+    fn splice<'a>(
+        &'a self,
+        path: &Path,
+        statements: Vec<lir::Statement<'a>>,
+        chain: &mut Vec<PathBuf>,
+    ) -> Result<Vec<lir::Statement<'a>>> {
+        let mut spliced = Vec::with_capacity(statements.len());
+        for statement in statements {
+            match unquote(&statement) {
+                // Included statements are quoted like the include itself
+                (depth, lir::Statement::Include(lir::IncludeStatement { path: include })) => {
+                    let included = self.parse_spliced(&resolve(path, include), chain)?;
+                    spliced.extend(
+                        included
+                            .statements
+                            .into_iter()
+                            .map(|statement| quote(statement, depth)),
+                    );
+                }
+                _ => spliced.push(statement),
+            }
+        }
+        Ok(spliced)
+    }
+
+    fn parse_file(&self, path: &Path, chain: &[PathBuf]) -> Result<lir::Document<'_>> {
+        // Every file reachable from the root was loaded by `Sources::load`
+        parser::lir::parse(&self.files[path]).map_err(|source| Error::Parse {
+            chain: chain.to_vec(),
+            source,
+        })
+    }
+}
+
+/// Collects the paths of includes in `statements`, quoted or not.
+fn collect_includes<'a>(statements: &[lir::Statement<'a>], includes: &mut Vec<&'a str>) {
+    for statement in statements {
+        if let (_, lir::Statement::Include(lir::IncludeStatement { path })) = unquote(statement) {
+            includes.push(path);
+        }
+    }
+}
+
+/// Returns the number of block quotes around `statement` and the statement
+/// inside of them.
+fn unquote<'s, 'a>(mut statement: &'s lir::Statement<'a>) -> (usize, &'s lir::Statement<'a>) {
+    let mut depth = 0;
+    while let lir::Statement::BlockQuote(lir::BlockQuoteStatement { statement: quoted }) = statement
+    {
+        statement = quoted;
+        depth += 1;
+    }
+    (depth, statement)
+}
+
+fn quote(mut statement: lir::Statement<'_>, depth: usize) -> lir::Statement<'_> {
+    for _ in 0..depth {
+        statement = lir::Statement::BlockQuote(lir::BlockQuoteStatement {
+            statement: Box::new(statement),
+        });
+    }
+    statement
+}
+
+/// Resolves `include` relative to the directory of the including file.
+fn resolve(including: &Path, include: &str) -> PathBuf {
+    let directory = including.parent().unwrap_or_else(|| Path::new(""));
+    normalize(&directory.join(include))
+}
+
+/// Removes `.` and `..` components without touching the file system, so
+/// that in-memory loaders see the same paths as the file system would.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => (),
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loader(files: &[(&str, &str)]) -> HashMap<PathBuf, String> {
+        files
+            .iter()
+            .map(|(path, source)| (PathBuf::from(path), source.to_string()))
+            .collect()
+    }
+
+    fn paragraph(text: &str) -> lir::Statement<'_> {
+        lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text(text.into())],
+            },
+        })
+    }
+
+    #[test]
+    fn splice() {
+        let loader = loader(&[
+            ("book/main.w4", "a\n@include \"chapters/one.w4\"\nd"),
+            ("book/chapters/one.w4", "b\n@include \"../shared/note.w4\""),
+            ("book/shared/note.w4", "c\n"),
+        ]);
+
+        let sources = Sources::load(Path::new("book/./main.w4"), &loader).unwrap();
+        let expected = lir::Document {
+            metadata: Default::default(),
+            statements: vec![
+                paragraph("a"),
+                paragraph("b"),
+                paragraph("c"),
+                paragraph("d"),
+            ],
+        };
+        assert_eq!(sources.parse(Path::new("book/main.w4")).unwrap(), expected);
+    }
+
+    #[test]
+    fn splice_quoted() {
+        let loader = loader(&[
+            ("main.w4", "> > @include \"quote.w4\""),
+            ("quote.w4", "a\nb"),
+        ]);
+        let sources = Sources::load(Path::new("main.w4"), &loader).unwrap();
+        let quoted = |statement| quote(statement, 2);
+        let expected = lir::Document {
+            metadata: Default::default(),
+            statements: vec![quoted(paragraph("a")), quoted(paragraph("b"))],
+        };
+        assert_eq!(sources.parse(Path::new("main.w4")).unwrap(), expected);
+    }
+
+    #[test]
+    fn fs_loader_root() {
+        let dir = std::env::temp_dir().join(format!("writer4-include-{}", std::process::id()));
+        let root = dir.join("root");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("a.w4"), "a").unwrap();
+        std::fs::write(dir.join("secret.w4"), "secret").unwrap();
+
+        let loader = FsLoader::with_root(&root);
+        assert_eq!(loader.load(&root.join("a.w4")).unwrap(), "a");
+        let error = loader.load(&root.join("../secret.w4")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(FsLoader::default().load(&root.join("../secret.w4")).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn errors() {
+        let loader = loader(&[
+            ("a.w4", "@include \"b.w4\""),
+            ("b.w4", "@include \"a.w4\""),
+            ("c.w4", "@include \"missing.w4\""),
+        ]);
+
+        let error = Sources::load(Path::new("a.w4"), &loader).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "include cycle `a.w4` -> `b.w4` -> `a.w4`"
+        );
+
+        let error = Sources::load(Path::new("c.w4"), &loader).unwrap_err();
+        assert_eq!(error.to_string(), "cannot load `c.w4` -> `missing.w4`");
+    }
+}
@@ -1,24 +1,62 @@
 use crate::ast::lir;
-use pest::{iterators::Pair, prec_climber::PrecClimber, Parser};
-use std::convert::TryFrom;
+use pest::{
+    iterators::Pair,
+    prec_climber::{Assoc, Operator, PrecClimber},
+    Parser,
+};
+use std::{borrow::Cow, convert::TryFrom};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("parsing error")]
-    Pest(#[from] pest::error::Error<Rule>),
+    Pest(#[from] Box<pest::error::Error<Rule>>),
+    #[error("malformed condition `{0}`")]
+    MalformedCondition(String),
+    #[error("attribute `{0}` is given more than once")]
+    DuplicateAttribute(String),
+    #[error("table of contents levels `{0}..{1}` are in reverse order")]
+    ReversedTableOfContentsLevels(u32, u32),
+}
+
+impl From<pest::error::Error<Rule>> for Error {
+    fn from(error: pest::error::Error<Rule>) -> Self {
+        Error::Pest(Box::new(error))
+    }
 }
 
 pub fn parse(code: &str) -> Result<lir::Document<'_>> {
     // Parse code as document pair
     let document = Writer4Parser::parse(Rule::document, code)?.next().unwrap();
 
-    // Create prec climber
-    let prec = PrecClimber::new(vec![]);
-
     // Parse pair to ast
-    Ok(lir::Document::parse(document, &prec)?)
+    lir::Document::parse(document, &prec_climber())
+}
+
+/// Operators of conditions, from the lowest to the highest precedence.
+fn prec_climber() -> PrecClimber<Rule> {
+    PrecClimber::new(vec![
+        Operator::new(Rule::condition_or, Assoc::Left),
+        Operator::new(Rule::condition_and, Assoc::Left),
+    ])
+}
+
+/// Parses only the front matter of `code`, ignoring the rest of the document.
+pub fn parse_metadata(code: &str) -> lir::Metadata<'_> {
+    // Neither step can fail as the front matter itself is optional
+    let front_matter = Writer4Parser::parse(Rule::front_matter, code)
+        .unwrap()
+        .next()
+        .unwrap();
+
+    match front_matter
+        .into_inner()
+        .find(|pair| pair.as_rule() == Rule::metadata)
+    {
+        Some(metadata) => lir::Metadata::parse(metadata, &prec_climber()).unwrap(),
+        None => lir::Metadata::default(),
+    }
 }
 
 #[derive(pest_derive::Parser)]
@@ -39,37 +77,108 @@ where
     fn parse(pair: Pair<'a, Rule>, prec: &PrecClimber<Rule>) -> Result<Self> {
         let pairs = pair.into_inner();
 
-        Ok(pairs
-            .map(|pair| T::parse(pair, prec))
-            .collect::<Result<_>>()?)
+        pairs.map(|pair| T::parse(pair, prec)).collect()
     }
 }
 
 impl<'a> Parse<'a> for lir::Document<'a> {
     fn parse(pair: Pair<'a, Rule>, prec: &PrecClimber<Rule>) -> Result<Self> {
-        let document = pair.into_inner();
+        let mut metadata = lir::Metadata::default();
+        let mut statements = Vec::new();
 
-        let statements = document
-            .filter(|pair| match pair.as_rule() {
-                Rule::statement => true,
-                Rule::EOI => false,
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::metadata => metadata = lir::Metadata::parse(pair, prec)?,
+                Rule::statement => statements.push(lir::Statement::parse(pair, prec)?),
+                Rule::EOI => (),
                 _ => unreachable!(),
+            }
+        }
+
+        Ok(lir::Document {
+            metadata,
+            statements,
+        })
+    }
+}
+
+impl<'a> Parse<'a> for lir::Metadata<'a> {
+    fn parse(pair: Pair<'a, Rule>, _prec: &PrecClimber<Rule>) -> Result<Self> {
+        let entries = pair
+            .into_inner()
+            .filter(|pair| pair.as_rule() == Rule::metadata_entry)
+            .map(|entry| {
+                let mut entry = entry.into_inner();
+                let key = entry.next().unwrap().as_str();
+                let value = entry.next().unwrap();
+                let value = match value.as_rule() {
+                    Rule::metadata_quoted_value => value.as_str(),
+                    _ => value.as_str().trim_end(),
+                };
+                (key, value)
             })
-            .map(|pair| lir::Statement::parse(pair, prec))
-            .collect::<Result<_>>()?;
+            .collect();
+
+        Ok(lir::Metadata { entries })
+    }
+}
+
+impl<'a> Parse<'a> for lir::Attributes<'a> {
+    fn parse(pair: Pair<'a, Rule>, _prec: &PrecClimber<Rule>) -> Result<Self> {
+        let mut attributes = lir::Attributes::default();
+        // Keys seen so far, as HTML doesn't allow repeating them
+        let mut keys: Vec<&str> = Vec::new();
+
+        let mut pairs = pair.into_inner();
+        while let Some(pair) = pairs.next() {
+            let key = match pair.as_rule() {
+                Rule::id => {
+                    attributes.id = Some(pair.as_str());
+                    "id"
+                }
+                Rule::attribute_class => {
+                    attributes.classes.push(pair.as_str());
+                    continue;
+                }
+                Rule::attribute_key => {
+                    let value = pairs.next().unwrap().as_str();
+                    // Spelled out IDs and classes mean the same as the shorthands
+                    match pair.as_str().to_ascii_lowercase().as_str() {
+                        "id" => attributes.id = Some(value),
+                        "class" => attributes.classes.extend(value.split_whitespace()),
+                        _ => attributes.pairs.push((pair.as_str(), value)),
+                    }
+                    pair.as_str()
+                }
+                _ => unreachable!(),
+            };
+
+            if keys.iter().any(|key_| key_.eq_ignore_ascii_case(key)) {
+                return Err(Error::DuplicateAttribute(key.to_string()));
+            }
+            keys.push(key);
+        }
 
-        Ok(lir::Document { statements })
+        Ok(attributes)
     }
 }
 
 impl<'a> Parse<'a> for lir::Statement<'a> {
     fn parse(pair: Pair<'a, Rule>, prec: &PrecClimber<Rule>) -> Result<Self> {
-        let statement = pair.into_inner().next().unwrap();
+        let mut pairs = pair.into_inner();
+        let mut statement = pairs.next().unwrap();
+
+        // Each `>` of the prefix adds a level of quoting
+        let mut depth = 0;
+        if statement.as_rule() == Rule::quote_prefix {
+            depth = statement.as_str().matches('>').count();
+            statement = pairs.next().unwrap();
+        }
 
-        let statement = match statement.as_rule() {
+        let mut statement = match statement.as_rule() {
             Rule::empty_line_statement => lir::Statement::EmptyLine(lir::EmptyLineStatement),
             Rule::comment_statement => lir::Statement::Comment(lir::CommentStatement {
-                text: statement.into_inner().next().unwrap().as_str(),
+                text: unquoted(statement.into_inner().next().unwrap()),
             }),
             Rule::header_statement => {
                 let mut header_statement = statement.into_inner();
@@ -78,13 +187,20 @@ impl<'a> Parse<'a> for lir::Statement<'a> {
                     let pair = header_statement.next().unwrap();
                     match pair.as_rule() {
                         Rule::number_sign => header_type += 1,
-                        Rule::text => break lir::Text::parse(pair, prec)?,
+                        Rule::header_text => break lir::Text::parse(pair, prec)?,
                         _ => unreachable!(),
                     }
                 };
+                let attributes = match header_statement.next() {
+                    Some(pair) if pair.as_rule() == Rule::attributes => {
+                        lir::Attributes::parse(pair, prec)?
+                    }
+                    _ => lir::Attributes::default(),
+                };
 
                 lir::Statement::Header(lir::HeaderStatement {
                     header_type: lir::HeaderType::try_from(header_type).unwrap(),
+                    attributes,
                     text,
                 })
             }
@@ -95,58 +211,318 @@ impl<'a> Parse<'a> for lir::Statement<'a> {
                 let mut list_item_statement = statement.into_inner();
 
                 let indentation = list_item_statement.next().unwrap();
-                let text = match list_item_statement.next() {
-                    Some(pair) if pair.as_rule() == Rule::text => lir::Text::parse(pair, prec)?,
-                    _ => lir::Text {
-                        segments: Vec::new(),
-                    },
+                let marker = list_item_statement.next().unwrap();
+                let marker = match marker.as_rule() {
+                    Rule::list_bullet => lir::ListMarker::Bullet,
+                    Rule::list_number => lir::ListMarker::Number(marker.as_str().parse().unwrap()),
+                    _ => unreachable!(),
                 };
+                let mut checked = None;
+                let mut text = lir::Text {
+                    segments: Vec::new(),
+                };
+                for pair in list_item_statement {
+                    match pair.as_rule() {
+                        Rule::task_checked => checked = Some(true),
+                        Rule::task_unchecked => checked = Some(false),
+                        Rule::text => text = lir::Text::parse(pair, prec)?,
+                        Rule::EOI => (),
+                        _ => unreachable!(),
+                    }
+                }
 
                 lir::Statement::ListItem(lir::ListItemStatement {
                     indentation: indentation.as_str().len(),
+                    marker,
+                    checked,
                     text,
                 })
             }
             Rule::horizontal_rule_statement => {
                 lir::Statement::HorizontalRule(lir::HorizontalRuleStatement)
             }
+            Rule::figure_statement => {
+                let mut figure_statement = statement.into_inner();
+
+                let image = lir::Image::parse(figure_statement.next().unwrap(), prec)?;
+                let caption = match figure_statement.next() {
+                    Some(pair) if pair.as_rule() == Rule::text => lir::Text::parse(pair, prec)?,
+                    _ => lir::Text {
+                        segments: Vec::new(),
+                    },
+                };
+
+                lir::Statement::Figure(lir::FigureStatement { image, caption })
+            }
+            Rule::variable_definition_statement => {
+                let mut variable_definition_statement = statement.into_inner();
+
+                lir::Statement::VariableDefinition(lir::VariableDefinitionStatement {
+                    name: variable_definition_statement.next().unwrap().as_str(),
+                    value: variable_definition_statement
+                        .next()
+                        .unwrap()
+                        .as_str()
+                        .trim_end(),
+                })
+            }
+            Rule::toc_statement => {
+                let mut levels = statement
+                    .into_inner()
+                    .filter(|pair| pair.as_rule() == Rule::toc_level)
+                    .map(|pair| pair.as_str().parse::<u32>().unwrap());
+                let min_level = levels.next().unwrap_or(1);
+                let max_level = levels.next().unwrap_or(6);
+                if min_level > max_level {
+                    return Err(Error::ReversedTableOfContentsLevels(min_level, max_level));
+                }
+
+                lir::Statement::TableOfContents(lir::TableOfContentsStatement {
+                    min_level: lir::HeaderType::try_from(min_level).unwrap(),
+                    max_level: lir::HeaderType::try_from(max_level).unwrap(),
+                })
+            }
+            Rule::leaf_directive_statement => {
+                let (name, label, arguments) = parse_directive(statement, prec)?;
+                lir::Statement::Directive(lir::DirectiveStatement {
+                    name,
+                    label,
+                    arguments,
+                })
+            }
+            Rule::conditional_statement => {
+                let mut pairs = statement.into_inner();
+                let keyword = pairs.next().unwrap().as_rule();
+                let mut condition = || lir::Condition::parse(pairs.next().unwrap(), prec);
+                lir::Statement::Conditional(match keyword {
+                    Rule::conditional_if => lir::ConditionalStatement::If(condition()?),
+                    Rule::conditional_elif => lir::ConditionalStatement::Elif(condition()?),
+                    Rule::conditional_else => lir::ConditionalStatement::Else,
+                    Rule::conditional_end => lir::ConditionalStatement::End,
+                    _ => unreachable!(),
+                })
+            }
+            Rule::attributes_statement => lir::Statement::Attributes(lir::AttributesStatement {
+                attributes: lir::Attributes::parse(statement.into_inner().next().unwrap(), prec)?,
+            }),
+            Rule::bibliography_statement => {
+                lir::Statement::Bibliography(lir::BibliographyStatement)
+            }
+            Rule::include_statement => lir::Statement::Include(lir::IncludeStatement {
+                path: statement.into_inner().next().unwrap().as_str(),
+            }),
+            Rule::definition_statement => lir::Statement::Definition(lir::DefinitionStatement {
+                text: lir::Text::parse(statement.into_inner().next().unwrap(), prec)?,
+            }),
+            Rule::math_block_statement => {
+                let content = match unquoted(statement.into_inner().next().unwrap()) {
+                    Cow::Borrowed(content) => Cow::Borrowed(content.trim()),
+                    Cow::Owned(content) => Cow::Owned(content.trim().to_string()),
+                };
+                lir::Statement::MathBlock(lir::MathBlockStatement { content })
+            }
+            Rule::admonition_start_statement => {
+                let mut kind = "";
+                let mut arguments = lir::Attributes::default();
+                let mut title = None;
+                for pair in statement.into_inner() {
+                    match pair.as_rule() {
+                        Rule::directive_name => kind = pair.as_str(),
+                        Rule::attributes => arguments = lir::Attributes::parse(pair, prec)?,
+                        Rule::text => {
+                            let text = lir::Text::parse(pair, prec)?;
+                            if !text.segments.is_empty() {
+                                title = Some(text);
+                            }
+                        }
+                        Rule::EOI => (),
+                        _ => unreachable!(),
+                    }
+                }
+                lir::Statement::AdmonitionStart(lir::AdmonitionStartStatement {
+                    kind,
+                    arguments,
+                    title,
+                })
+            }
+            Rule::admonition_end_statement => {
+                lir::Statement::AdmonitionEnd(lir::AdmonitionEndStatement)
+            }
+            Rule::table_row_statement => lir::Statement::TableRow(lir::TableRowStatement {
+                cells: statement
+                    .into_inner()
+                    .filter(|pair| pair.as_rule() == Rule::table_cell)
+                    .map(|pair| lir::Text::parse(pair, prec))
+                    .collect::<Result<_>>()?,
+            }),
+            Rule::table_separator_statement => {
+                let alignments = statement
+                    .into_inner()
+                    .filter(|pair| pair.as_rule() == Rule::table_alignment)
+                    .map(|pair| {
+                        let mut left = false;
+                        let mut right = false;
+                        for pair in pair.into_inner() {
+                            match pair.as_rule() {
+                                Rule::table_align_left => left = true,
+                                Rule::table_align_right => right = true,
+                                _ => unreachable!(),
+                            }
+                        }
+
+                        match (left, right) {
+                            (false, false) => None,
+                            (true, false) => Some(lir::Alignment::Left),
+                            (true, true) => Some(lir::Alignment::Center),
+                            (false, true) => Some(lir::Alignment::Right),
+                        }
+                    })
+                    .collect();
+
+                lir::Statement::TableSeparator(lir::TableSeparatorStatement { alignments })
+            }
+            Rule::footnote_definition_statement => {
+                let mut footnote_definition_statement = statement.into_inner();
+
+                let label = footnote_definition_statement.next().unwrap().as_str();
+                let text = match footnote_definition_statement.next() {
+                    Some(pair) if pair.as_rule() == Rule::text => lir::Text::parse(pair, prec)?,
+                    _ => lir::Text {
+                        segments: Vec::new(),
+                    },
+                };
+
+                lir::Statement::FootnoteDefinition(lir::FootnoteDefinitionStatement { label, text })
+            }
+            Rule::code_block_statement => {
+                let mut info = None;
+                let mut content = Cow::Borrowed("");
+                for pair in statement.into_inner() {
+                    match pair.as_rule() {
+                        Rule::code_block_info => info = Some(pair.as_str().trim_end()),
+                        Rule::code_block_content => content = unquoted(pair),
+                        Rule::quote_continuation | Rule::EOI => (),
+                        _ => unreachable!(),
+                    }
+                }
+
+                lir::Statement::CodeBlock(lir::CodeBlockStatement { info, content })
+            }
+            Rule::raw_block_statement => {
+                let mut pairs = statement.into_inner();
+
+                lir::Statement::Raw(lir::RawStatement {
+                    target: pairs.next().unwrap().as_str(),
+                    content: unquoted(pairs.next().unwrap()),
+                })
+            }
             _ => unreachable!(),
         };
 
+        for _ in 0..depth {
+            statement = lir::Statement::BlockQuote(lir::BlockQuoteStatement {
+                statement: Box::new(statement),
+            });
+        }
+
         Ok(statement)
     }
 }
 
-impl<'a> Parse<'a> for lir::Text<'a> {
-    fn parse(pair: Pair<'a, Rule>, _: &PrecClimber<Rule>) -> Result<Self> {
-        assert!(pair.as_rule() == Rule::text);
-        let text = pair.into_inner();
-
-        let mut segments = text
-            .map(|pair| match pair.as_rule() {
-                Rule::emph_bold => lir::TextSegment::Emphasis(lir::Emphasis::Bold),
-                Rule::emph_italic => lir::TextSegment::Emphasis(lir::Emphasis::Italic),
-                Rule::emph_strikethrough => {
-                    lir::TextSegment::Emphasis(lir::Emphasis::Strikethrough)
+/// Returns the text of `pair` without the quote prefixes of its lines.
+fn unquoted<'a>(pair: Pair<'a, Rule>) -> Cow<'a, str> {
+    let text = pair.as_str();
+    let offset = pair.as_span().start();
+    let prefixes: Vec<_> = pair
+        .into_inner()
+        .filter(|pair| pair.as_rule() == Rule::quote_continuation && !pair.as_str().is_empty())
+        .map(|pair| pair.as_span())
+        .collect();
+    if prefixes.is_empty() {
+        return Cow::Borrowed(text);
+    }
+
+    let mut unquoted = String::with_capacity(text.len());
+    let mut start = 0;
+    for prefix in prefixes {
+        unquoted += &text[start..prefix.start() - offset];
+        start = prefix.end() - offset;
+    }
+    unquoted += &text[start..];
+    Cow::Owned(unquoted)
+}
+
+impl<'a> Parse<'a> for lir::Condition<'a> {
+    fn parse(pair: Pair<'a, Rule>, prec: &PrecClimber<Rule>) -> Result<Self> {
+        let condition = match pair.as_rule() {
+            Rule::condition => {
+                let pair = pair.into_inner().next().unwrap();
+                match pair.as_rule() {
+                    Rule::condition_expression => lir::Condition::parse(pair, prec)?,
+                    Rule::condition_malformed => {
+                        return Err(Error::MalformedCondition(
+                            pair.as_str().trim_end().to_string(),
+                        ))
+                    }
+                    _ => unreachable!(),
                 }
-                Rule::text_segment => lir::TextSegment::Text(pair.as_str()),
-                _ => unreachable!(),
-            })
-            .collect::<Vec<_>>();
+            }
+            Rule::condition_expression => prec.climb(
+                pair.into_inner(),
+                |pair| lir::Condition::parse(pair, prec),
+                |lhs, op, rhs| {
+                    let (lhs, rhs) = (Box::new(lhs?), Box::new(rhs?));
+                    Ok(match op.as_rule() {
+                        Rule::condition_and => lir::Condition::And(lhs, rhs),
+                        Rule::condition_or => lir::Condition::Or(lhs, rhs),
+                        _ => unreachable!(),
+                    })
+                },
+            )?,
+            Rule::condition_negation => lir::Condition::Not(Box::new(lir::Condition::parse(
+                pair.into_inner().next().unwrap(),
+                prec,
+            )?)),
+            Rule::condition_comparison => {
+                let mut comparison = pair.into_inner();
+
+                let flag = comparison.next().unwrap().as_str();
+                let operator = comparison.next().unwrap();
+                let value = comparison.next().unwrap().as_str();
+                match operator.as_rule() {
+                    Rule::condition_equal => lir::Condition::Equal(flag, value),
+                    Rule::condition_not_equal => lir::Condition::NotEqual(flag, value),
+                    _ => unreachable!(),
+                }
+            }
+            Rule::condition_flag => {
+                lir::Condition::Flag(pair.into_inner().next().unwrap().as_str())
+            }
+            _ => unreachable!(),
+        };
+
+        Ok(condition)
+    }
+}
+
+impl<'a> Parse<'a> for lir::Text<'a> {
+    fn parse(pair: Pair<'a, Rule>, prec: &PrecClimber<Rule>) -> Result<Self> {
+        assert!(matches!(
+            pair.as_rule(),
+            Rule::text | Rule::header_text | Rule::table_cell
+        ));
+
+        let mut segments = parse_text_segments(pair, prec)?;
 
         if let Some(lir::TextSegment::Text(text)) = segments.last_mut() {
-            let trailing_white_spaces = text
-                .chars()
-                .rev()
-                .position(|c| c != ' ' && c != '\t')
-                .unwrap_or(text.len());
-
-            match trailing_white_spaces {
-                0 => (),
-                trailing_white_spaces if trailing_white_spaces == text.len() => {
+            let len = text.trim_end_matches([' ', '\t']).len();
+
+            match text {
+                _ if len == 0 => {
                     segments.pop();
                 }
-                trailing_white_spaces => *text = &text[0..text.len() - trailing_white_spaces],
+                Cow::Borrowed(text) => *text = &text[0..len],
+                Cow::Owned(text) => text.truncate(len),
             }
         }
 
@@ -154,15 +530,160 @@ impl<'a> Parse<'a> for lir::Text<'a> {
     }
 }
 
+/// Parses the inner pairs of `pair` as text segments, joining adjacent plain
+/// text so that escaped characters end up in the text around them.
+fn parse_text_segments<'a>(
+    pair: Pair<'a, Rule>,
+    prec: &PrecClimber<Rule>,
+) -> Result<Vec<lir::TextSegment<'a>>> {
+    let mut segments: Vec<lir::TextSegment> = Vec::new();
+
+    for segment in Vec::<lir::TextSegment>::parse(pair, prec)? {
+        match (segments.last_mut(), segment) {
+            (Some(lir::TextSegment::Text(text)), lir::TextSegment::Text(next)) => {
+                text.to_mut().push_str(&next)
+            }
+            (_, segment) => segments.push(segment),
+        }
+    }
+
+    Ok(segments)
+}
+
+impl<'a> Parse<'a> for lir::TextSegment<'a> {
+    fn parse(pair: Pair<'a, Rule>, prec: &PrecClimber<Rule>) -> Result<Self> {
+        let segment = match pair.as_rule() {
+            Rule::emph_bold => lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+            Rule::emph_italic => lir::TextSegment::Emphasis(lir::Emphasis::Italic),
+            Rule::emph_strikethrough => lir::TextSegment::Emphasis(lir::Emphasis::Strikethrough),
+            Rule::emph_underline => lir::TextSegment::Emphasis(lir::Emphasis::Underline),
+            Rule::emph_mark => lir::TextSegment::Emphasis(lir::Emphasis::Mark),
+            Rule::emph_superscript => lir::TextSegment::Emphasis(lir::Emphasis::Superscript),
+            Rule::emph_subscript => lir::TextSegment::Emphasis(lir::Emphasis::Subscript),
+            Rule::text_segment
+            | Rule::header_text_segment
+            | Rule::link_label_segment
+            | Rule::table_cell_segment
+            | Rule::escaped_char => lir::TextSegment::Text(Cow::Borrowed(pair.as_str())),
+            Rule::link => {
+                let mut link = pair.into_inner();
+
+                let label = parse_text_segments(link.next().unwrap(), prec)?;
+                let url = link.next().unwrap().as_str();
+                let title = link.next().map(|pair| pair.as_str());
+
+                lir::TextSegment::Link { label, url, title }
+            }
+            Rule::cross_reference => {
+                lir::TextSegment::CrossReference(pair.into_inner().next().unwrap().as_str())
+            }
+            Rule::variable => {
+                lir::TextSegment::Variable(pair.into_inner().next().unwrap().as_str())
+            }
+            Rule::inline_math => lir::TextSegment::Math(pair.into_inner().next().unwrap().as_str()),
+            Rule::footnote_reference => {
+                lir::TextSegment::FootnoteReference(pair.into_inner().next().unwrap().as_str())
+            }
+            Rule::span => {
+                let mut span = pair.into_inner();
+
+                lir::TextSegment::Span {
+                    text: parse_text_segments(span.next().unwrap(), prec)?,
+                    attributes: lir::Attributes::parse(span.next().unwrap(), prec)?,
+                }
+            }
+            Rule::inline_directive => {
+                let (name, label, arguments) = parse_directive(pair, prec)?;
+                lir::TextSegment::Directive {
+                    name,
+                    label,
+                    arguments,
+                }
+            }
+            Rule::citation => lir::TextSegment::Citation(
+                pair.into_inner()
+                    .map(|item| {
+                        let mut item = item.into_inner();
+                        lir::Citation {
+                            key: item.next().unwrap().as_str(),
+                            locator: item.next().map(|locator| locator.as_str().trim_end()),
+                        }
+                    })
+                    .collect(),
+            ),
+            Rule::image => lir::TextSegment::Image(lir::Image::parse(pair, prec)?),
+            Rule::code_span => {
+                let content = pair.into_inner().next().unwrap().as_str();
+
+                // One surrounding space is stripped so that code starting or
+                // ending with a backtick can be written as `` `x ``
+                let content = if content.starts_with(' ')
+                    && content.ends_with(' ')
+                    && content.chars().any(|c| c != ' ')
+                {
+                    &content[1..content.len() - 1]
+                } else {
+                    content
+                };
+
+                lir::TextSegment::Code(content)
+            }
+            Rule::autolink => lir::TextSegment::Link {
+                label: vec![lir::TextSegment::Text(Cow::Borrowed(pair.as_str()))],
+                url: pair.as_str(),
+                title: None,
+            },
+            _ => unreachable!(),
+        };
+
+        Ok(segment)
+    }
+}
+
+impl<'a> Parse<'a> for lir::Image<'a> {
+    fn parse(pair: Pair<'a, Rule>, _: &PrecClimber<Rule>) -> Result<Self> {
+        assert!(pair.as_rule() == Rule::image);
+        let mut image = pair.into_inner();
+
+        let alt = image.next().unwrap().as_str();
+        let url = image.next().unwrap().as_str();
+        let title = image.next().map(|pair| pair.as_str());
+
+        Ok(lir::Image { alt, url, title })
+    }
+}
+
+/// Parses the name, label and arguments shared by inline and leaf directives.
+fn parse_directive<'a>(
+    pair: Pair<'a, Rule>,
+    prec: &PrecClimber<Rule>,
+) -> Result<(&'a str, Vec<lir::TextSegment<'a>>, lir::Attributes<'a>)> {
+    let mut name = "";
+    let mut label = Vec::new();
+    let mut arguments = lir::Attributes::default();
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::directive_name => name = pair.as_str(),
+            Rule::directive_label => {
+                label = parse_text_segments(pair.into_inner().next().unwrap(), prec)?
+            }
+            Rule::attributes => arguments = lir::Attributes::parse(pair, prec)?,
+            Rule::EOI => (),
+            _ => unreachable!(),
+        }
+    }
+    Ok((name, label, arguments))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn prec() -> PrecClimber<Rule> {
-        PrecClimber::new(vec![])
+        prec_climber()
     }
 
-    fn statement_pair(code: &str) -> Pair<Rule> {
+    fn statement_pair(code: &str) -> Pair<'_, Rule> {
         Writer4Parser::parse(Rule::statement, code)
             .unwrap()
             .next()
@@ -191,17 +712,23 @@ mod tests {
     #[test]
     fn comment() {
         let pair = statement_pair(r###"//"###);
-        let expected = lir::Statement::Comment(lir::CommentStatement { text: "" });
+        let expected = lir::Statement::Comment(lir::CommentStatement { text: "".into() });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
 
         let pair = statement_pair(r###"// Hello World"###);
         let expected = lir::Statement::Comment(lir::CommentStatement {
-            text: " Hello World",
+            text: " Hello World".into(),
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
 
         let pair = statement_pair(r###"  //xxx"###);
-        let expected = lir::Statement::Comment(lir::CommentStatement { text: "xxx" });
+        let expected = lir::Statement::Comment(lir::CommentStatement { text: "xxx".into() });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("/* Hello\n// World */  \nx");
+        let expected = lir::Statement::Comment(lir::CommentStatement {
+            text: " Hello\n// World ".into(),
+        });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
     }
 
@@ -210,8 +737,9 @@ mod tests {
         let pair = statement_pair(r###"# Hello World"###);
         let expected = lir::Statement::Header(lir::HeaderStatement {
             header_type: lir::HeaderType::H1,
+            attributes: lir::Attributes::default(),
             text: lir::Text {
-                segments: vec![lir::TextSegment::Text("Hello World")],
+                segments: vec![lir::TextSegment::Text("Hello World".into())],
             },
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
@@ -219,8 +747,9 @@ mod tests {
         let pair = statement_pair(r###"##   Subtitle"###);
         let expected = lir::Statement::Header(lir::HeaderStatement {
             header_type: lir::HeaderType::H2,
+            attributes: lir::Attributes::default(),
             text: lir::Text {
-                segments: vec![lir::TextSegment::Text("Subtitle")],
+                segments: vec![lir::TextSegment::Text("Subtitle".into())],
             },
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
@@ -228,8 +757,9 @@ mod tests {
         let pair = statement_pair(r###"  ###   Subtitle "###);
         let expected = lir::Statement::Header(lir::HeaderStatement {
             header_type: lir::HeaderType::H3,
+            attributes: lir::Attributes::default(),
             text: lir::Text {
-                segments: vec![lir::TextSegment::Text("Subtitle")],
+                segments: vec![lir::TextSegment::Text("Subtitle".into())],
             },
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
@@ -237,10 +767,11 @@ mod tests {
         let pair = statement_pair(r###"######   __%&%}[{~~__"###);
         let expected = lir::Statement::Header(lir::HeaderStatement {
             header_type: lir::HeaderType::H6,
+            attributes: lir::Attributes::default(),
             text: lir::Text {
                 segments: vec![
                     lir::TextSegment::Emphasis(lir::Emphasis::Italic),
-                    lir::TextSegment::Text("%&%}[{"),
+                    lir::TextSegment::Text("%&%}[{".into()),
                     lir::TextSegment::Emphasis(lir::Emphasis::Strikethrough),
                     lir::TextSegment::Emphasis(lir::Emphasis::Italic),
                 ],
@@ -251,10 +782,11 @@ mod tests {
         let pair = statement_pair(r###"###  ~~Strikethrough~~  "###);
         let expected = lir::Statement::Header(lir::HeaderStatement {
             header_type: lir::HeaderType::H3,
+            attributes: lir::Attributes::default(),
             text: lir::Text {
                 segments: vec![
                     lir::TextSegment::Emphasis(lir::Emphasis::Strikethrough),
-                    lir::TextSegment::Text("Strikethrough"),
+                    lir::TextSegment::Text("Strikethrough".into()),
                     lir::TextSegment::Emphasis(lir::Emphasis::Strikethrough),
                 ],
             },
@@ -267,7 +799,7 @@ mod tests {
         let pair = statement_pair(r###"lorem ipsum"###);
         let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
             text: lir::Text {
-                segments: vec![lir::TextSegment::Text("lorem ipsum")],
+                segments: vec![lir::TextSegment::Text("lorem ipsum".into())],
             },
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
@@ -275,7 +807,7 @@ mod tests {
         let pair = statement_pair(r###"trailing tabs		"###);
         let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
             text: lir::Text {
-                segments: vec![lir::TextSegment::Text("trailing tabs")],
+                segments: vec![lir::TextSegment::Text("trailing tabs".into())],
             },
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
@@ -284,13 +816,13 @@ mod tests {
         let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
             text: lir::Text {
                 segments: vec![
-                    lir::TextSegment::Text("lorem "),
+                    lir::TextSegment::Text("lorem ".into()),
                     lir::TextSegment::Emphasis(lir::Emphasis::Italic),
                     lir::TextSegment::Emphasis(lir::Emphasis::Bold),
-                    lir::TextSegment::Text("ipsum"),
+                    lir::TextSegment::Text("ipsum".into()),
                     lir::TextSegment::Emphasis(lir::Emphasis::Bold),
                     lir::TextSegment::Emphasis(lir::Emphasis::Italic),
-                    lir::TextSegment::Text("!"),
+                    lir::TextSegment::Text("!".into()),
                 ],
             },
         });
@@ -299,7 +831,7 @@ mod tests {
         let pair = statement_pair(r###"#not a header"###);
         let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
             text: lir::Text {
-                segments: vec![lir::TextSegment::Text("#not a header")],
+                segments: vec![lir::TextSegment::Text("#not a header".into())],
             },
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
@@ -307,7 +839,7 @@ mod tests {
         let pair = statement_pair(r###"-not a list"###);
         let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
             text: lir::Text {
-                segments: vec![lir::TextSegment::Text("-not a list")],
+                segments: vec![lir::TextSegment::Text("-not a list".into())],
             },
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
@@ -315,7 +847,7 @@ mod tests {
         let pair = statement_pair(r###"--- --"###);
         let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
             text: lir::Text {
-                segments: vec![lir::TextSegment::Text("--- --")],
+                segments: vec![lir::TextSegment::Text("--- --".into())],
             },
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
@@ -326,8 +858,10 @@ mod tests {
         let pair = statement_pair(r###"- list item"###);
         let expected = lir::Statement::ListItem(lir::ListItemStatement {
             indentation: 0,
+            marker: lir::ListMarker::Bullet,
+            checked: None,
             text: lir::Text {
-                segments: vec![lir::TextSegment::Text("list item")],
+                segments: vec![lir::TextSegment::Text("list item".into())],
             },
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
@@ -335,8 +869,10 @@ mod tests {
         let pair = statement_pair(r###"  -     list item"###);
         let expected = lir::Statement::ListItem(lir::ListItemStatement {
             indentation: 2,
+            marker: lir::ListMarker::Bullet,
+            checked: None,
             text: lir::Text {
-                segments: vec![lir::TextSegment::Text("list item")],
+                segments: vec![lir::TextSegment::Text("list item".into())],
             },
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
@@ -344,16 +880,103 @@ mod tests {
         let pair = statement_pair(r###"   - list **item**"###);
         let expected = lir::Statement::ListItem(lir::ListItemStatement {
             indentation: 3,
+            marker: lir::ListMarker::Bullet,
+            checked: None,
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("list ".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                    lir::TextSegment::Text("item".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn ordered_list_item() {
+        let pair = statement_pair(r###"1. first"###);
+        let expected = lir::Statement::ListItem(lir::ListItemStatement {
+            indentation: 0,
+            marker: lir::ListMarker::Number(1),
+            checked: None,
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text("first".into())],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"  42)   answer"###);
+        let expected = lir::Statement::ListItem(lir::ListItemStatement {
+            indentation: 2,
+            marker: lir::ListMarker::Number(42),
+            checked: None,
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text("answer".into())],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"7."###);
+        let expected = lir::Statement::ListItem(lir::ListItemStatement {
+            indentation: 0,
+            marker: lir::ListMarker::Number(7),
+            checked: None,
+            text: lir::Text { segments: vec![] },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"1.5 is not a list"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text("1.5 is not a list".into())],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn task_list_item() {
+        let pair = statement_pair(r###"- [ ] write **tests**"###);
+        let expected = lir::Statement::ListItem(lir::ListItemStatement {
+            indentation: 0,
+            marker: lir::ListMarker::Bullet,
+            checked: Some(false),
             text: lir::Text {
                 segments: vec![
-                    lir::TextSegment::Text("list "),
+                    lir::TextSegment::Text("write ".into()),
                     lir::TextSegment::Emphasis(lir::Emphasis::Bold),
-                    lir::TextSegment::Text("item"),
+                    lir::TextSegment::Text("tests".into()),
                     lir::TextSegment::Emphasis(lir::Emphasis::Bold),
                 ],
             },
         });
         assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"  2. [X]"###);
+        let expected = lir::Statement::ListItem(lir::ListItemStatement {
+            indentation: 2,
+            marker: lir::ListMarker::Number(2),
+            checked: Some(true),
+            text: lir::Text { segments: vec![] },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"- [x](https://example.com)"###);
+        let expected = lir::Statement::ListItem(lir::ListItemStatement {
+            indentation: 0,
+            marker: lir::ListMarker::Bullet,
+            checked: None,
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Link {
+                    label: vec![lir::TextSegment::Text("x".into())],
+                    url: "https://example.com",
+                    title: None,
+                }],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
     }
 
     #[test]
@@ -376,13 +999,801 @@ mod tests {
     }
 
     #[test]
-    fn empty() {
-        let code = r###""###;
-        let expected = lir::Document { statements: vec![] };
+    fn link() {
+        let pair = statement_pair(r###"see [the **docs**](https://example.com "Docs") now"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("see ".into()),
+                    lir::TextSegment::Link {
+                        label: vec![
+                            lir::TextSegment::Text("the ".into()),
+                            lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                            lir::TextSegment::Text("docs".into()),
+                            lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                        ],
+                        url: "https://example.com",
+                        title: Some("Docs"),
+                    },
+                    lir::TextSegment::Text(" now".into()),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"- [](/a/b)"###);
+        let expected = lir::Statement::ListItem(lir::ListItemStatement {
+            indentation: 0,
+            marker: lir::ListMarker::Bullet,
+            checked: None,
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Link {
+                    label: vec![],
+                    url: "/a/b",
+                    title: None,
+                }],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"[not a link] (x)"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text("[not a link] (x)".into())],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn autolink() {
+        let pair = statement_pair(r###"visit https://example.com/?q=1, or http://a.b."###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("visit ".into()),
+                    lir::TextSegment::Link {
+                        label: vec![lir::TextSegment::Text("https://example.com/?q=1".into())],
+                        url: "https://example.com/?q=1",
+                        title: None,
+                    },
+                    lir::TextSegment::Text(", or ".into()),
+                    lir::TextSegment::Link {
+                        label: vec![lir::TextSegment::Text("http://a.b".into())],
+                        url: "http://a.b",
+                        title: None,
+                    },
+                    lir::TextSegment::Text(".".into()),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn image() {
+        let pair = statement_pair(r###"icon ![a cat](img/cat.png "Cat") here"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("icon ".into()),
+                    lir::TextSegment::Image(lir::Image {
+                        alt: "a cat",
+                        url: "img/cat.png",
+                        title: Some("Cat"),
+                    }),
+                    lir::TextSegment::Text(" here".into()),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn figure() {
+        let pair = statement_pair(r###"!![Overview](arch.svg) The **architecture**"###);
+        let expected = lir::Statement::Figure(lir::FigureStatement {
+            image: lir::Image {
+                alt: "Overview",
+                url: "arch.svg",
+                title: None,
+            },
+            caption: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("The ".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                    lir::TextSegment::Text("architecture".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"  !![](a.png)  "###);
+        let expected = lir::Statement::Figure(lir::FigureStatement {
+            image: lir::Image {
+                alt: "",
+                url: "a.png",
+                title: None,
+            },
+            caption: lir::Text { segments: vec![] },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn code_span() {
+        let pair = statement_pair(r###"call `__init__` or ``a ` b`` or `` `x` ``"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("call ".into()),
+                    lir::TextSegment::Code("__init__"),
+                    lir::TextSegment::Text(" or ".into()),
+                    lir::TextSegment::Code("a ` b"),
+                    lir::TextSegment::Text(" or ".into()),
+                    lir::TextSegment::Code("`x`"),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"**`a**b`** and `unclosed"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                    lir::TextSegment::Code("a**b"),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                    lir::TextSegment::Text(" and `unclosed".into()),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn code_block() {
+        let pair = statement_pair("```rust no_run\nfn main() {\n    // **x**\n}\n```");
+        let expected = lir::Statement::CodeBlock(lir::CodeBlockStatement {
+            info: Some("rust no_run"),
+            content: "fn main() {\n    // **x**\n}\n".into(),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("````\n```\n\n````  \n");
+        let expected = lir::Statement::CodeBlock(lir::CodeBlockStatement {
+            info: None,
+            content: "```\n\n".into(),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("```\n```");
+        let expected = lir::Statement::CodeBlock(lir::CodeBlockStatement {
+            info: None,
+            content: "".into(),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("```unclosed\ncode");
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text("```unclosed".into())],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn block_quote() {
+        let pair = statement_pair(r###"> # Quoted"###);
+        let expected = lir::Statement::BlockQuote(lir::BlockQuoteStatement {
+            statement: Box::new(lir::Statement::Header(lir::HeaderStatement {
+                header_type: lir::HeaderType::H1,
+                attributes: lir::Attributes::default(),
+                text: lir::Text {
+                    segments: vec![lir::TextSegment::Text("Quoted".into())],
+                },
+            })),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"  >> -  item"###);
+        let expected = lir::Statement::BlockQuote(lir::BlockQuoteStatement {
+            statement: Box::new(lir::Statement::BlockQuote(lir::BlockQuoteStatement {
+                statement: Box::new(lir::Statement::ListItem(lir::ListItemStatement {
+                    indentation: 0,
+                    marker: lir::ListMarker::Bullet,
+                    checked: None,
+                    text: lir::Text {
+                        segments: vec![lir::TextSegment::Text("item".into())],
+                    },
+                })),
+            })),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###">"###);
+        let expected = lir::Statement::BlockQuote(lir::BlockQuoteStatement {
+            statement: Box::new(lir::Statement::EmptyLine(lir::EmptyLineStatement)),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("> ```\n> a\n>\n>  b\n> ```");
+        let expected = lir::Statement::BlockQuote(lir::BlockQuoteStatement {
+            statement: Box::new(lir::Statement::CodeBlock(lir::CodeBlockStatement {
+                info: None,
+                content: "a\n\n b\n".into(),
+            })),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("> ```\na\n```");
+        let expected = lir::Statement::BlockQuote(lir::BlockQuoteStatement {
+            statement: Box::new(lir::Statement::Paragraph(lir::ParagraphStatement {
+                text: lir::Text {
+                    segments: vec![lir::TextSegment::Text("```".into())],
+                },
+            })),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###">.42"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text(">.42".into())],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn table() {
+        let pair = statement_pair(r###"| Name | **Age** |  |"###);
+        let expected = lir::Statement::TableRow(lir::TableRowStatement {
+            cells: vec![
+                lir::Text {
+                    segments: vec![lir::TextSegment::Text("Name".into())],
+                },
+                lir::Text {
+                    segments: vec![
+                        lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                        lir::TextSegment::Text("Age".into()),
+                        lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                    ],
+                },
+                lir::Text { segments: vec![] },
+            ],
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###" |---|:--| :-: |--:| "###);
+        let expected = lir::Statement::TableSeparator(lir::TableSeparatorStatement {
+            alignments: vec![
+                None,
+                Some(lir::Alignment::Left),
+                Some(lir::Alignment::Center),
+                Some(lir::Alignment::Right),
+            ],
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"| not closed"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text("| not closed".into())],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn footnote() {
+        let pair = statement_pair(r###"Claim[^1] and [^long-note]."###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("Claim".into()),
+                    lir::TextSegment::FootnoteReference("1"),
+                    lir::TextSegment::Text(" and ".into()),
+                    lir::TextSegment::FootnoteReference("long-note"),
+                    lir::TextSegment::Text(".".into()),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"[^long-note]: See __chapter 2__"###);
+        let expected = lir::Statement::FootnoteDefinition(lir::FootnoteDefinitionStatement {
+            label: "long-note",
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("See ".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Italic),
+                    lir::TextSegment::Text("chapter 2".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Italic),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn escape() {
+        let pair = statement_pair(r###"\# a \*\*b\** \\ c\ d"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text("# a **b** \\ c\\ d".into())],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"\- \// [a\]](b) `\*`"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("- // ".into()),
+                    lir::TextSegment::Link {
+                        label: vec![lir::TextSegment::Text("a]".into())],
+                        url: "b",
+                        title: None,
+                    },
+                    lir::TextSegment::Text(" ".into()),
+                    lir::TextSegment::Code("\\*"),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn definition() {
+        let pair = statement_pair(r###"  : A **short** explanation"###);
+        let expected = lir::Statement::Definition(lir::DefinitionStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("A ".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                    lir::TextSegment::Text("short".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                    lir::TextSegment::Text(" explanation".into()),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###": not indented"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text(": not indented".into())],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn admonition() {
+        let pair = statement_pair(":::warning Mind the **gap**");
+        let expected = lir::Statement::AdmonitionStart(lir::AdmonitionStartStatement {
+            kind: "warning",
+            arguments: lir::Attributes::default(),
+            title: Some(lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("Mind the ".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                    lir::TextSegment::Text("gap".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Bold),
+                ],
+            }),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(" :::  ");
+        let expected = lir::Statement::AdmonitionEnd(lir::AdmonitionEndStatement);
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        // Openers without `:::` don't make the parser look ahead for one
+        let code = ":::note\n".repeat(500);
+        let document = parse(&code).unwrap();
+        assert_eq!(document.statements.len(), 500);
+    }
+
+    #[test]
+    fn extended_emphasis() {
+        let pair = statement_pair(r###"++a++ ==b== x^2^ H~2~O ~~c~~"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Emphasis(lir::Emphasis::Underline),
+                    lir::TextSegment::Text("a".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Underline),
+                    lir::TextSegment::Text(" ".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Mark),
+                    lir::TextSegment::Text("b".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Mark),
+                    lir::TextSegment::Text(" x".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Superscript),
+                    lir::TextSegment::Text("2".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Superscript),
+                    lir::TextSegment::Text(" H".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Subscript),
+                    lir::TextSegment::Text("2".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Subscript),
+                    lir::TextSegment::Text("O ".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Strikethrough),
+                    lir::TextSegment::Text("c".into()),
+                    lir::TextSegment::Emphasis(lir::Emphasis::Strikethrough),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn math() {
+        let pair = statement_pair(r###"Area $\pi r^2$ costs $5 or $10, \$x\$"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("Area ".into()),
+                    lir::TextSegment::Math("\\pi r^2"),
+                    lir::TextSegment::Text(" costs $5 or $10, $x$".into()),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("$$\n\\frac{a}{b}\n$$");
+        let expected = lir::Statement::MathBlock(lir::MathBlockStatement {
+            content: "\\frac{a}{b}".into(),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("$$ stray\n\nText $$");
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text("$$ stray".into())],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn header_id() {
+        let pair = statement_pair(r###"## Install {#install-guide}"###);
+        let expected = lir::Statement::Header(lir::HeaderStatement {
+            header_type: lir::HeaderType::H2,
+            attributes: lir::Attributes {
+                id: Some("install-guide"),
+                ..lir::Attributes::default()
+            },
+            text: lir::Text {
+                segments: vec![lir::TextSegment::Text("Install".into())],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"# Sets {#a} are <<sets>>"###);
+        let expected = lir::Statement::Header(lir::HeaderStatement {
+            header_type: lir::HeaderType::H1,
+            attributes: lir::Attributes::default(),
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("Sets {#a} are ".into()),
+                    lir::TextSegment::CrossReference("sets"),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn table_of_contents() {
+        let pair = statement_pair(r###"@toc"###);
+        let expected = lir::Statement::TableOfContents(lir::TableOfContentsStatement {
+            min_level: lir::HeaderType::H1,
+            max_level: lir::HeaderType::H6,
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"@toc 2..3"###);
+        let expected = lir::Statement::TableOfContents(lir::TableOfContentsStatement {
+            min_level: lir::HeaderType::H2,
+            max_level: lir::HeaderType::H3,
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"@toc 3..3"###);
+        let expected = lir::Statement::TableOfContents(lir::TableOfContentsStatement {
+            min_level: lir::HeaderType::H3,
+            max_level: lir::HeaderType::H3,
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"@toc 3..2"###);
+        assert!(matches!(
+            lir::Statement::parse(pair, &prec()),
+            Err(Error::ReversedTableOfContentsLevels(3, 2))
+        ));
+    }
+
+    #[test]
+    fn citation() {
+        let pair = statement_pair(r###"See [@doe2020, p. 12 ; @roe] and [@x](url)"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("See ".into()),
+                    lir::TextSegment::Citation(vec![
+                        lir::Citation {
+                            key: "doe2020",
+                            locator: Some("p. 12"),
+                        },
+                        lir::Citation {
+                            key: "roe",
+                            locator: None,
+                        },
+                    ]),
+                    lir::TextSegment::Text(" and ".into()),
+                    lir::TextSegment::Link {
+                        label: vec![lir::TextSegment::Text("@x".into())],
+                        url: "url",
+                        title: None,
+                    },
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn raw() {
+        let pair = statement_pair("````{=html}\n<b>```</b>\n````");
+        let expected = lir::Statement::Raw(lir::RawStatement {
+            target: "html",
+            content: "<b>```</b>\n".into(),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("```{=html}x\n```");
+        let expected = lir::Statement::CodeBlock(lir::CodeBlockStatement {
+            info: Some("{=html}x"),
+            content: "".into(),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn conditional() {
+        let pair = statement_pair("@if !draft && (edition == \"pro\" || beta)");
+        let expected =
+            lir::Statement::Conditional(lir::ConditionalStatement::If(lir::Condition::And(
+                Box::new(lir::Condition::Not(Box::new(lir::Condition::Flag("draft")))),
+                Box::new(lir::Condition::Or(
+                    Box::new(lir::Condition::Equal("edition", "pro")),
+                    Box::new(lir::Condition::Flag("beta")),
+                )),
+            )));
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("  @elif a || b && c");
+        let expected =
+            lir::Statement::Conditional(lir::ConditionalStatement::Elif(lir::Condition::Or(
+                Box::new(lir::Condition::Flag("a")),
+                Box::new(lir::Condition::And(
+                    Box::new(lir::Condition::Flag("b")),
+                    Box::new(lir::Condition::Flag("c")),
+                )),
+            )));
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("@else ");
+        let expected = lir::Statement::Conditional(lir::ConditionalStatement::Else);
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("@end");
+        let expected = lir::Statement::Conditional(lir::ConditionalStatement::End);
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair("@if edition = pro");
+        assert!(matches!(
+            lir::Statement::parse(pair, &prec()),
+            Err(Error::MalformedCondition(condition)) if condition == "edition = pro"
+        ));
+
+        // Openers without `@end` don't make the parser look ahead for one
+        let code = "@if a\n".repeat(500);
+        let document = parse(&code).unwrap();
+        assert_eq!(document.statements.len(), 500);
+    }
+
+    #[test]
+    fn attributes() {
+        let pair =
+            statement_pair(r###"{ #intro .lead .x class="a b" data-x=1 title="Hi there" }"###);
+        let expected = lir::Statement::Attributes(lir::AttributesStatement {
+            attributes: lir::Attributes {
+                id: Some("intro"),
+                classes: vec!["lead", "x", "a", "b"],
+                pairs: vec![("data-x", "1"), ("title", "Hi there")],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        for code in [
+            "{#a id=b}",
+            "{ID=a #b}",
+            "{class=a class=b}",
+            "{title=a Title=b}",
+        ]
+        .iter()
+        {
+            let pair = statement_pair(code);
+            assert!(matches!(
+                lir::Statement::parse(pair, &prec()),
+                Err(Error::DuplicateAttribute(_))
+            ));
+        }
+
+        let pair = statement_pair(r###"A [__b__]{.c} {{d}} [e]"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("A ".into()),
+                    lir::TextSegment::Span {
+                        text: vec![
+                            lir::TextSegment::Emphasis(lir::Emphasis::Italic),
+                            lir::TextSegment::Text("b".into()),
+                            lir::TextSegment::Emphasis(lir::Emphasis::Italic),
+                        ],
+                        attributes: lir::Attributes {
+                            classes: vec!["c"],
+                            ..lir::Attributes::default()
+                        },
+                    },
+                    lir::TextSegment::Text(" ".into()),
+                    lir::TextSegment::Variable("d"),
+                    lir::TextSegment::Text(" [e]".into()),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn directive() {
+        let pair = statement_pair(r###"::video[A __cat__]{src=cat.mp4}"###);
+        let expected = lir::Statement::Directive(lir::DirectiveStatement {
+            name: "video",
+            label: vec![
+                lir::TextSegment::Text("A ".into()),
+                lir::TextSegment::Emphasis(lir::Emphasis::Italic),
+                lir::TextSegment::Text("cat".into()),
+                lir::TextSegment::Emphasis(lir::Emphasis::Italic),
+            ],
+            arguments: lir::Attributes {
+                pairs: vec![("src", "cat.mp4")],
+                ..lir::Attributes::default()
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"Press :kbd[Ctrl] at 10:30 :smile:"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("Press ".into()),
+                    lir::TextSegment::Directive {
+                        name: "kbd",
+                        label: vec![lir::TextSegment::Text("Ctrl".into())],
+                        arguments: lir::Attributes::default(),
+                    },
+                    lir::TextSegment::Text(" at 10:30 :smile:".into()),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(":::details{.wide} More");
+        let expected = lir::Statement::AdmonitionStart(lir::AdmonitionStartStatement {
+            kind: "details",
+            arguments: lir::Attributes {
+                classes: vec!["wide"],
+                ..lir::Attributes::default()
+            },
+            title: Some(lir::Text {
+                segments: vec![lir::TextSegment::Text("More".into())],
+            }),
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn variable() {
+        let pair = statement_pair(r###"@set product-name = Writer 4 "###);
+        let expected = lir::Statement::VariableDefinition(lir::VariableDefinitionStatement {
+            name: "product-name",
+            value: "Writer 4",
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+
+        let pair = statement_pair(r###"Get {{ product-name }} {{version}} `{{raw}}`"###);
+        let expected = lir::Statement::Paragraph(lir::ParagraphStatement {
+            text: lir::Text {
+                segments: vec![
+                    lir::TextSegment::Text("Get ".into()),
+                    lir::TextSegment::Variable("product-name"),
+                    lir::TextSegment::Text(" ".into()),
+                    lir::TextSegment::Variable("version"),
+                    lir::TextSegment::Text(" ".into()),
+                    lir::TextSegment::Code("{{raw}}"),
+                ],
+            },
+        });
+        assert_eq!(lir::Statement::parse(pair, &prec()).unwrap(), expected);
+    }
+
+    #[test]
+    fn metadata() {
+        let code = "---\ntitle: \"Hello: World\"\nauthor: Jane Doe  \n\ntags: [a, b]\n---\nText";
+        let expected = lir::Document {
+            metadata: lir::Metadata {
+                entries: vec![
+                    ("title", "Hello: World"),
+                    ("author", "Jane Doe"),
+                    ("tags", "[a, b]"),
+                ],
+            },
+            statements: vec![lir::Statement::Paragraph(lir::ParagraphStatement {
+                text: lir::Text {
+                    segments: vec![lir::TextSegment::Text("Text".into())],
+                },
+            })],
+        };
+        assert_eq!(parse(code).unwrap(), expected);
+        assert_eq!(parse_metadata(code), expected.metadata);
+        assert_eq!(expected.metadata.tags(), vec!["a", "b"]);
+
+        let code = "---\nnot metadata\n---";
+        let expected = lir::Document {
+            metadata: Default::default(),
+            statements: vec![
+                lir::Statement::HorizontalRule(lir::HorizontalRuleStatement),
+                lir::Statement::Paragraph(lir::ParagraphStatement {
+                    text: lir::Text {
+                        segments: vec![lir::TextSegment::Text("not metadata".into())],
+                    },
+                }),
+                lir::Statement::HorizontalRule(lir::HorizontalRuleStatement),
+            ],
+        };
+        assert_eq!(parse(code).unwrap(), expected);
+        assert_eq!(parse_metadata(code), Default::default());
+
+        let code = "---\n---";
+        let expected = lir::Document {
+            metadata: Default::default(),
+            statements: vec![
+                lir::Statement::HorizontalRule(lir::HorizontalRuleStatement),
+                lir::Statement::HorizontalRule(lir::HorizontalRuleStatement),
+            ],
+        };
+        assert_eq!(parse(code).unwrap(), expected);
+        assert_eq!(parse_metadata(code), Default::default());
+    }
+
+    #[test]
+    fn empty() {
+        let code = r###""###;
+        let expected = lir::Document {
+            metadata: Default::default(),
+            statements: vec![],
+        };
         assert_eq!(parse(code).unwrap(), expected);
 
         let code = r###" "###;
         let expected = lir::Document {
+            metadata: Default::default(),
             statements: vec![lir::Statement::EmptyLine(lir::EmptyLineStatement)],
         };
         assert_eq!(parse(code).unwrap(), expected);
@@ -413,88 +1824,102 @@ alpha beta 123!
 ## h2
 ..."###;
         let expected = lir::Document {
+            metadata: Default::default(),
             statements: vec![
                 lir::Statement::EmptyLine(lir::EmptyLineStatement),
                 lir::Statement::Header(lir::HeaderStatement {
                     header_type: lir::HeaderType::H1,
+                    attributes: lir::Attributes::default(),
                     text: lir::Text {
                         segments: vec![
                             lir::TextSegment::Emphasis(lir::Emphasis::Strikethrough),
-                            lir::TextSegment::Text("Hello Wor"),
+                            lir::TextSegment::Text("Hello Wor".into()),
                             lir::TextSegment::Emphasis(lir::Emphasis::Italic),
-                            lir::TextSegment::Text("ld"),
+                            lir::TextSegment::Text("ld".into()),
                         ],
                     },
                 }),
                 lir::Statement::EmptyLine(lir::EmptyLineStatement),
                 lir::Statement::Comment(lir::CommentStatement {
-                    text: " a random comment: 2 + 2 = 4",
+                    text: " a random comment: 2 + 2 = 4".into(),
                 }),
                 lir::Statement::Paragraph(lir::ParagraphStatement {
                     text: lir::Text {
-                        segments: vec![lir::TextSegment::Text("-a,b,c,d,e")],
+                        segments: vec![lir::TextSegment::Text("-a,b,c,d,e".into())],
                     },
                 }),
                 lir::Statement::Paragraph(lir::ParagraphStatement {
                     text: lir::Text {
-                        segments: vec![lir::TextSegment::Text("lorem ipsum")],
+                        segments: vec![lir::TextSegment::Text("lorem ipsum".into())],
                     },
                 }),
                 lir::Statement::Paragraph(lir::ParagraphStatement {
                     text: lir::Text {
-                        segments: vec![lir::TextSegment::Text("alpha beta 123!")],
+                        segments: vec![lir::TextSegment::Text("alpha beta 123!".into())],
                     },
                 }),
                 lir::Statement::ListItem(lir::ListItemStatement {
                     indentation: 0,
+                    marker: lir::ListMarker::Bullet,
+                    checked: None,
                     text: lir::Text {
-                        segments: vec![lir::TextSegment::Text("A")],
+                        segments: vec![lir::TextSegment::Text("A".into())],
                     },
                 }),
                 lir::Statement::ListItem(lir::ListItemStatement {
                     indentation: 0,
+                    marker: lir::ListMarker::Bullet,
+                    checked: None,
                     text: lir::Text {
                         segments: vec![
                             lir::TextSegment::Emphasis(lir::Emphasis::Strikethrough),
-                            lir::TextSegment::Text("B"),
+                            lir::TextSegment::Text("B".into()),
                             lir::TextSegment::Emphasis(lir::Emphasis::Strikethrough),
                         ],
                     },
                 }),
                 lir::Statement::ListItem(lir::ListItemStatement {
                     indentation: 2,
+                    marker: lir::ListMarker::Bullet,
+                    checked: None,
                     text: lir::Text {
-                        segments: vec![lir::TextSegment::Text("C")],
+                        segments: vec![lir::TextSegment::Text("C".into())],
                     },
                 }),
                 lir::Statement::ListItem(lir::ListItemStatement {
                     indentation: 2,
+                    marker: lir::ListMarker::Bullet,
+                    checked: None,
                     text: lir::Text {
-                        segments: vec![lir::TextSegment::Text("D")],
+                        segments: vec![lir::TextSegment::Text("D".into())],
                     },
                 }),
                 lir::Statement::Paragraph(lir::ParagraphStatement {
                     text: lir::Text {
                         segments: vec![
                             lir::TextSegment::Emphasis(lir::Emphasis::Bold),
-                            lir::TextSegment::Text("12345"),
+                            lir::TextSegment::Text("12345".into()),
                             lir::TextSegment::Emphasis(lir::Emphasis::Bold),
-                            lir::TextSegment::Text("67890"),
+                            lir::TextSegment::Text("67890".into()),
                         ],
                     },
                 }),
-                lir::Statement::Comment(lir::CommentStatement { text: "" }),
+                lir::Statement::Comment(lir::CommentStatement { text: "".into() }),
                 lir::Statement::ListItem(lir::ListItemStatement {
                     indentation: 0,
+                    marker: lir::ListMarker::Bullet,
+                    checked: None,
                     text: lir::Text {
-                        segments: vec![lir::TextSegment::Text("E")],
+                        segments: vec![lir::TextSegment::Text("E".into())],
                     },
                 }),
                 lir::Statement::EmptyLine(lir::EmptyLineStatement),
                 lir::Statement::ListItem(lir::ListItemStatement {
                     indentation: 0,
+                    marker: lir::ListMarker::Bullet,
+                    checked: None,
                     text: lir::Text {
-                        segments: vec![lir::TextSegment::Text("F")],
+                        segments: vec![lir::TextSegment::Text("F".into())],
                     },
                 }),
                 lir::Statement::EmptyLine(lir::EmptyLineStatement),
@@ -503,13 +1928,14 @@ alpha beta 123!
                 lir::Statement::EmptyLine(lir::EmptyLineStatement),
                 lir::Statement::Header(lir::HeaderStatement {
                     header_type: lir::HeaderType::H2,
+                    attributes: lir::Attributes::default(),
                     text: lir::Text {
-                        segments: vec![lir::TextSegment::Text("h2")],
+                        segments: vec![lir::TextSegment::Text("h2".into())],
                     },
                 }),
                 lir::Statement::Paragraph(lir::ParagraphStatement {
                     text: lir::Text {
-                        segments: vec![lir::TextSegment::Text("...")],
+                        segments: vec![lir::TextSegment::Text("...".into())],
                     },
                 }),
             ],
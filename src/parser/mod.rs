@@ -1,2 +1,4 @@
+pub mod bibliography;
 pub mod hir;
+pub mod include;
 pub mod lir;
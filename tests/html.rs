@@ -75,10 +75,897 @@ K, L, M
 
 "###;
 
-    let expected_output = r###"<div class="writer4-doc"><h1>Hello <s>World</s>!</h1><h2>Subtitle</h2><h3>...</h3><h4>...</h4><h5>...</h5><h6>...</h6><p>Lorem ipsum <b>dolor</b> sit amet, consetetur sadipscing elitr,<br>sed diam nonumy eirmod tempor invidunt ut labore et dolore magna aliquyam erat,<br>sed diam <i>voluptua</i>.</p><p>Lorem ipsum dolor sit amet, consetetur sadipscing elitr, sed diam nonumy eirmod tempor invidunt ut labore et dolore magna aliquyam erat, sed diam voluptua.</p><p>Red <b>Green<br>Blue</b> Yellow</p><ul><li>Red</li><li><b><i><s>Green</s></i></b>, <s>White<br>Lorem</s> ipsum <i>dolor sit amet,<br>consetetur sadipscing</i> elitr, ...</li><li>Blue</li></ul><ul><li>A</li><li>B</li><li>C<ul><li>D</li><li>E</li><li></li><li>F</li><li><br>G, H, I, J<br>K, L, M</li><li>N<ul><li>O<ul><li>P<br>-Q</li></ul></li></ul></li><li>R</li></ul></li><li></li><li>S, T, U, V, W<ul><li>X, Y, Z</li></ul></li></ul><hr><p>~~...__<br>####### ...<br>###Hello World</p><ul><li>A</li><li>B<ul><li>C</li></ul></li><li>D<br>...</li><li>E</li><li>F<br>-G</li><li></li><li>H</li><li>I<ul><li>J</li></ul></li><li>K</li></ul><ul><li>L</li><li>M</li><li>N<ul><li>O</li><li>P</li></ul></li></ul><h3>&lt;i&gt;Hello&lt;/i&gt;</h3><p>&lt;b&gt;bold&lt;/b&gt;<br>&lt;s&gt;...&lt;<br>&gt;.42</p></div>"###;
+    let expected_output = r###"<div class="writer4-doc"><h1 id="hello-world">Hello <s>World</s>!</h1><h2 id="subtitle">Subtitle</h2><h3 id="section">...</h3><h4 id="section-1">...</h4><h5 id="section-2">...</h5><h6 id="section-3">...</h6><p>Lorem ipsum <b>dolor</b> sit amet, consetetur sadipscing elitr,<br>sed diam nonumy eirmod tempor invidunt ut labore et dolore magna aliquyam erat,<br>sed diam <i>voluptua</i>.</p><p>Lorem ipsum dolor sit amet, consetetur sadipscing elitr, sed diam nonumy eirmod tempor invidunt ut labore et dolore magna aliquyam erat, sed diam voluptua.</p><p>Red <b>Green<br>Blue</b> Yellow</p><ul><li>Red</li><li><b><i><s>Green</s></i></b>, <s>White<br>Lorem</s> ipsum <i>dolor sit amet,<br>consetetur sadipscing</i> elitr, ...</li><li>Blue</li></ul><ul><li>A</li><li>B</li><li>C<ul><li>D</li><li>E</li><li></li><li>F</li><li><br>G, H, I, J<br>K, L, M</li><li>N<ul><li>O<ul><li>P<br>-Q</li></ul></li></ul></li><li>R</li></ul></li><li></li><li>S, T, U, V, W<ul><li>X, Y, Z</li></ul></li></ul><hr><p>~~...__<br>####### ...<br>###Hello World</p><ul><li>A</li><li>B<ul><li>C</li></ul></li><li>D<br>...</li><li>E</li><li>F<br>-G</li><li></li><li>H</li><li>I<ul><li>J</li></ul></li><li>K</li></ul><ul><li>L</li><li>M</li><li>N<ul><li>O</li><li>P</li></ul></li></ul><h3 id="ihelloi">&lt;i&gt;Hello&lt;/i&gt;</h3><p>&lt;b&gt;bold&lt;/b&gt;<br>&lt;s&gt;...&lt;<br>&gt;.42</p></div>"###;
 
     assert_eq!(
         writer4_compiler::compile_html(source_code).unwrap(),
         expected_output
     );
 }
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn ordered_list() {
+    let source_code = r###"
+1. Preheat the oven
+2) Mix **flour** and water
+   - slowly
+   - carefully
+3. Bake
+
+4. Cool down
+   1. first hour
+   2. second hour
+- A
+  3. B
+  - C
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><ol><li>Preheat the oven</li><li>Mix <b>flour</b> and water<ul><li>slowly</li><li>carefully</li></ul></li><li>Bake</li></ol><ol start="4"><li>Cool down<ol><li>first hour</li><li>second hour</li></ol></li></ol><ul><li>A<ol start="3"><li>B</li></ol><ul><li>C</li></ul></li></ul></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn links() {
+    let source_code = r###"
+Read [the __fine__ manual](https://example.com/a?b=1&c=2 "Q&A <1>") or https://writer4.dev.
+[**Bold** link](<script>)
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>Read <a href="https://example.com/a?b=1&amp;c=2" title="Q&amp;A &lt;1&gt;">the <i>fine</i> manual</a> or <a href="https://writer4.dev">https://writer4.dev</a>.<br><a href="&lt;script&gt;"><b>Bold</b> link</a></p></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn images() {
+    let source_code = r###"
+Press ![the "save" icon](icons/save.png "Save") to save.
+
+!![Screenshot](shots/main.png "Main window") The __main__ window
+!![Diagram](diagram.svg)
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>Press <img src="icons/save.png" alt="the &quot;save&quot; icon" title="Save"> to save.</p><figure><img src="shots/main.png" alt="Screenshot" title="Main window"><figcaption>The <i>main</i> window</figcaption></figure><figure><img src="diagram.svg" alt="Diagram"></figure></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn code_spans() {
+    let source_code = r###"
+Override `__init__` and `~~del~~`, never **`<script>`**.
+- [`Vec::new`](https://doc.rust-lang.org/std/vec/struct.Vec.html)
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>Override <code>__init__</code> and <code>~~del~~</code>, never <b><code>&lt;script&gt;</code></b>.</p><ul><li><a href="https://doc.rust-lang.org/std/vec/struct.Vec.html"><code>Vec::new</code></a></li></ul></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn code_blocks() {
+    let source_code = r###"
+Example:
+```rust
+fn main() {
+    println!("<__init__>");
+}
+```
+```
+
+  - not a list
+# not a header
+```
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>Example:</p><pre><code class="language-rust">fn main() {
+    println!("&lt;__init__&gt;");
+}
+</code></pre><pre><code>
+  - not a list
+# not a header
+</code></pre></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn block_quotes() {
+    let source_code = r###"
+> ## From the spec
+> The parser **must**
+> reject invalid input:
+> - empty files
+> - binary files
+>
+>> Nested quote
+> back
+3 > 2
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><blockquote><h2 id="from-the-spec">From the spec</h2><p>The parser <b>must</b><br>reject invalid input:</p><ul><li>empty files</li><li>binary files</li></ul><blockquote><p>Nested quote</p></blockquote><p>back</p></blockquote><p>3 &gt; 2</p></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    // Every line of a quoted fenced block repeats the prefix
+    let source_code = r###"
+> ```rust
+> let x = 1;
+>
+>     // > kept
+> ```
+> > $$
+> > a > b
+> > $$
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><blockquote><pre><code class="language-rust">let x = 1;
+
+    // &gt; kept
+</code></pre><blockquote><math display="block"><mi>a</mi><mo>&gt;</mo><mi>b</mi></math></blockquote></blockquote></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    let source_code = r###"
+> ```
+not quoted
+```
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><blockquote><p>```</p></blockquote><p>not quoted<br>```</p></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn tables() {
+    let source_code = r###"
+| Option | Default | Description |
+|:-------|:-------:|------------:|
+| `width` | 80 | Line **width** |
+| `tabs` | off | |
+
+| only | header |
+| --- | --- |
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><table><thead><tr><th style="text-align: left">Option</th><th style="text-align: center">Default</th><th style="text-align: right">Description</th></tr></thead><tbody><tr><td style="text-align: left"><code>width</code></td><td style="text-align: center">80</td><td style="text-align: right">Line <b>width</b></td></tr><tr><td style="text-align: left"><code>tabs</code></td><td style="text-align: center">off</td><td style="text-align: right"></td></tr></tbody></table><table><thead><tr><th>only</th><th>header</th></tr></thead></table></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    assert!(writer4_compiler::compile_html("|---|").is_err());
+    assert!(writer4_compiler::compile_html("| a |\n|---|\n| b |\n|---|\n| c |").is_err());
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn footnotes() {
+    let source_code = r###"
+Writer4 is fast[^speed] and small[^size].
+It is **really** fast[^speed].
+
+[^size]: Less than 2000 lines.
+[^speed]: Measured on
+a __laptop__.
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>Writer4 is fast<sup class="writer4-footnote-ref"><a id="fnref-1" href="#fn-1">1</a></sup> and small<sup class="writer4-footnote-ref"><a id="fnref-2" href="#fn-2">2</a></sup>.<br>It is <b>really</b> fast<sup class="writer4-footnote-ref"><a id="fnref-1-2" href="#fn-1">1</a></sup>.</p><section class="writer4-footnotes"><ol><li id="fn-1">Measured on<br>a <i>laptop</i>. <a class="writer4-footnote-backref" href="#fnref-1">↩</a> <a class="writer4-footnote-backref" href="#fnref-1-2">↩</a></li><li id="fn-2">Less than 2000 lines. <a class="writer4-footnote-backref" href="#fnref-2">↩</a></li></ol></section></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    assert!(writer4_compiler::compile_html("Dangling[^missing]").is_err());
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn task_lists() {
+    let source_code = r###"
+Action items:
+- [x] Send the minutes
+- [ ] Book a room
+  - [ ] ask facilities
+  - call the caterer
+- Plain item
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>Action items:</p><ul><li><input type="checkbox" disabled checked> Send the minutes</li><li><input type="checkbox" disabled> Book a room<ul><li><input type="checkbox" disabled> ask facilities</li><li>call the caterer</li></ul></li><li>Plain item</li></ul></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn escapes() {
+    let source_code = r###"
+\# Not a header
+\- not a list
+\// not a comment
+Literal \*\*stars\*\*, \_\_underscores\_\_ and \~\~tildes\~\~, but **bold \*** too.
+| a \| b | c |
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p># Not a header<br>- not a list<br>// not a comment<br>Literal **stars**, __underscores__ and ~~tildes~~, but <b>bold *</b> too.</p><table><tbody><tr><td>a | b</td><td>c</td></tr></tbody></table></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn math() {
+    let source_code = r###"
+Euler: $e^{i\pi} + 1 = 0$
+$$
+\sum_{k=1}^{n} k = \frac{n(n+1)}{2}
+$$
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>Euler: <math><msup><mi>e</mi><mrow><mi>i</mi><mi>π</mi></mrow></msup><mo>+</mo><mn>1</mn><mo>=</mo><mn>0</mn></math></p><math display="block"><munderover><mo>∑</mo><mrow><mi>k</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover><mi>k</mi><mo>=</mo><mfrac><mrow><mi>n</mi><mo>(</mo><mi>n</mi><mo>+</mo><mn>1</mn><mo>)</mo></mrow><mn>2</mn></mfrac></math></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+    let source_code = r###"
+$$ costs are stated in USD
+
+Price: 5 $$
+"###;
+
+    let expected_output =
+        r###"<div class="writer4-doc"><p>$$ costs are stated in USD</p><p>Price: 5 $$</p></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn extended_emphasis() {
+    let source_code = r###"
+++Underlined++, ==marked==, E = mc^2^ and H~2~O.
+Unpaired a == b stays text.
+
+C++ over Java, but C++ builds slower.
+It takes ~5 minutes, or ~10 at most.
+Check a == b and c == d first.
+Raise x^2 + y^2^ and keep ^ a^.
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p><u>Underlined</u>, <mark>marked</mark>, E = mc<sup>2</sup> and H<sub>2</sub>O.<br>Unpaired a == b stays text.</p><p>C++ over Java, but C++ builds slower.<br>It takes ~5 minutes, or ~10 at most.<br>Check a == b and c == d first.<br>Raise x^2 + y<sup>2</sup> and keep ^ a^.</p></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn definition_lists() {
+    let source_code = r###"
+Glossary:
+HIR
+  : High-level intermediate representation
+  : Built from the LIR
+
+LIR
+  : Line-based intermediate representation
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>Glossary:</p><dl><dt>HIR</dt><dd>High-level intermediate representation</dd><dd>Built from the LIR</dd><dt>LIR</dt><dd>Line-based intermediate representation</dd></dl></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+    assert_eq!(
+        writer4_compiler::compile_html("Ratio\n: 3").unwrap(),
+        r###"<div class="writer4-doc"><p>Ratio<br>: 3</p></div>"###
+    );
+    assert!(writer4_compiler::compile_html("Term\n\n  : Definition").is_err());
+    assert!(writer4_compiler::compile_html("- Item\n  : Definition").is_err());
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn admonitions() {
+    let source_code = r###"
+:::warning Breaking change
+The `--fast` flag was removed.
+
+:::note
+- Use `--release`
+:::
+:::
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><aside class="writer4-admonition writer4-warning"><p class="writer4-admonition-title">Breaking change</p><p>The <code>--fast</code> flag was removed.</p><aside class="writer4-admonition writer4-note"><ul><li>Use <code>--release</code></li></ul></aside></aside></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    assert_eq!(
+        writer4_compiler::compile_html(":::note{.wide #x} Title\nText\n:::").unwrap(),
+        r###"<div class="writer4-doc"><aside id="x" class="writer4-admonition writer4-note wide"><p class="writer4-admonition-title">Title</p><p>Text</p></aside></div>"###
+    );
+
+    assert!(writer4_compiler::compile_html(":::rumor\nText\n:::").is_err());
+    assert!(writer4_compiler::compile_html(":::note\nText").is_err());
+    assert!(writer4_compiler::compile_html("Text\n:::").is_err());
+    assert!(writer4_compiler::compile_html(&":::note\n".repeat(300)).is_err());
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn front_matter() {
+    let source_code = r###"---
+title: Handbook
+lang: de
+tags: internal, draft
+custom-key: custom value
+---
+Hallo
+"###;
+
+    let metadata = writer4_compiler::metadata(source_code);
+    assert_eq!(metadata.title(), Some("Handbook"));
+    assert_eq!(metadata.get("custom-key"), Some("custom value"));
+    assert_eq!(metadata.tags(), vec!["internal", "draft"]);
+
+    let expected_output = r###"<div class="writer4-doc" lang="de"><p>Hallo</p></div>"###;
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    let expected_output = r###"<!DOCTYPE html><html lang="de"><head><meta charset="utf-8"><title>Handbook</title><meta name="keywords" content="internal, draft"></head><body><div class="writer4-doc" lang="de"><p>Hallo</p></div></body></html>"###;
+    assert_eq!(
+        writer4_compiler::compile_html_page(source_code).unwrap(),
+        expected_output
+    );
+
+    let expected_output = r###"<div class="writer4-doc"><hr><hr></div>"###;
+    assert_eq!(
+        writer4_compiler::compile_html("---\n---\n").unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn includes() {
+    use std::{collections::HashMap, path::PathBuf};
+    use writer4_compiler::{Backend, HtmlBackend};
+
+    let mut files = HashMap::new();
+    files.insert(
+        PathBuf::from("handbook/index.w4"),
+        "# Handbook\n@include \"intro.w4\"\n".to_string(),
+    );
+    files.insert(
+        PathBuf::from("handbook/intro.w4"),
+        "Welcome!\n@include \"index.w4\"\n".to_string(),
+    );
+
+    let error =
+        HtmlBackend::compile_file("handbook/index.w4".as_ref(), &files, &Default::default())
+            .unwrap_err();
+    assert!(matches!(error, writer4_compiler::Error::Include(_)));
+
+    files.insert(PathBuf::from("handbook/intro.w4"), "Welcome!\n".to_string());
+    let expected_output =
+        r###"<div class="writer4-doc"><h1 id="handbook">Handbook</h1><p>Welcome!</p></div>"###;
+    assert_eq!(
+        HtmlBackend::compile_file("handbook/index.w4".as_ref(), &files, &Default::default())
+            .unwrap(),
+        expected_output
+    );
+
+    files.insert(
+        PathBuf::from("handbook/index.w4"),
+        "> @include \"intro.w4\"\n:::note\n@include \"intro.w4\"\n:::\n".to_string(),
+    );
+    let expected_output = r###"<div class="writer4-doc"><blockquote><p>Welcome!</p></blockquote><aside class="writer4-admonition writer4-note"><p>Welcome!</p></aside></div>"###;
+    assert_eq!(
+        HtmlBackend::compile_file("handbook/index.w4".as_ref(), &files, &Default::default())
+            .unwrap(),
+        expected_output
+    );
+
+    assert!(writer4_compiler::compile_html("@include \"intro.w4\"").is_err());
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn variables() {
+    use writer4_compiler::{Backend, HtmlBackend, Options};
+
+    let source_code = r###"
+@set product = Writer4
+@set version = 0.1
+{{product}} **{{ version }}** is out!
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>Writer4 <b>0.1</b> is out!</p></div>"###;
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    let mut options = Options::default();
+    options
+        .variables
+        .insert("version".to_string(), "1.0 <beta>".to_string());
+    let expected_output =
+        r###"<div class="writer4-doc"><p>Writer4 <b>1.0 &lt;beta&gt;</b> is out!</p></div>"###;
+    assert_eq!(
+        HtmlBackend::compile(source_code, &options).unwrap(),
+        expected_output
+    );
+
+    assert!(writer4_compiler::compile_html("{{undefined}}").is_err());
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn cross_references() {
+    let source_code = r###"
+# Getting Started
+See <<install>> and <<getting-started-1>>.
+## Install **it** {#install}
+# Getting Started
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><h1 id="getting-started">Getting Started</h1><p>See <a href="#install">Install <b>it</b></a> and <a href="#getting-started-1">Getting Started</a>.</p><h2 id="install">Install <b>it</b></h2><h1 id="getting-started-1">Getting Started</h1></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    assert!(writer4_compiler::compile_html("See <<nowhere>>").is_err());
+    assert!(writer4_compiler::compile_html("# A {#a}\n# B {#a}").is_err());
+
+    let source_code = r###"
+{#intro}
+See <<note>>, <<top>> and <<intro>>.
+:::tip{#note} Hint
+Text
+:::
+# A [__Top__]{#top}
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p id="intro">See <a href="#note">Hint</a>, <a href="#top"><i>Top</i></a> and <a href="#intro">intro</a>.</p><aside id="note" class="writer4-admonition writer4-tip"><p class="writer4-admonition-title">Hint</p><p>Text</p></aside><h1 id="a-top">A <span id="top"><i>Top</i></span></h1></div>"###;
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    assert!(writer4_compiler::compile_html("# A {#a}\n[x]{#a}").is_err());
+    assert!(writer4_compiler::compile_html("{#a}\nText\n:::note{#a}\n:::").is_err());
+
+    let source_code = r###"
+# [Install](https://example.com) it[^a] {#install}
+## Ref 1
+See <<install>>.
+[^a]: Note
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><h1 id="install"><a href="https://example.com">Install</a> it<sup class="writer4-footnote-ref"><a id="fnref-1" href="#fn-1">1</a></sup></h1><h2 id="section-ref-1">Ref 1</h2><p>See <a href="#install">Install it</a>.</p><section class="writer4-footnotes"><ol><li id="fn-1">Note <a class="writer4-footnote-backref" href="#fnref-1">↩</a></li></ol></section></div>"###;
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    assert_eq!(
+        writer4_compiler::compile_html("# A <<b>> {#a}\n# B <<a>> {#b}").unwrap(),
+        r###"<div class="writer4-doc"><h1 id="a">A <a href="#b">B A </a></h1><h1 id="b">B <a href="#a">A B </a></h1></div>"###
+    );
+
+    assert!(writer4_compiler::compile_html("# Notes {#fn-1}").is_err());
+    assert!(writer4_compiler::compile_html("[x]{#ref-doe2020}").is_err());
+}
+
+#[test]
+fn table_of_contents() {
+    let source_code = r###"
+# Title
+@toc 2..3
+## One[^a]
+### One A
+#### Deep
+## Two
+[^a]: Note
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><h1 id="title">Title</h1><nav class="writer4-toc"><ul><li><a href="#one">One</a><ul><li><a href="#one-a">One A</a></li></ul></li><li><a href="#two">Two</a></li></ul></nav><h2 id="one">One<sup class="writer4-footnote-ref"><a id="fnref-1" href="#fn-1">1</a></sup></h2><h3 id="one-a">One A</h3><h4 id="deep">Deep</h4><h2 id="two">Two</h2><section class="writer4-footnotes"><ol><li id="fn-1">Note <a class="writer4-footnote-backref" href="#fnref-1">↩</a></li></ol></section></div>"###;
+
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    assert_eq!(
+        writer4_compiler::compile_html("@toc\nText").unwrap(),
+        r###"<div class="writer4-doc"><nav class="writer4-toc"></nav><p>Text</p></div>"###
+    );
+    assert!(writer4_compiler::compile_html("@toc 3..2\n## A").is_err());
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn citations() {
+    use writer4_compiler::{Backend, Bibliography, CitationStyle, HtmlBackend, Options};
+
+    let bibtex = r###"
+@book{roe2019, author = {Roe, Richard}, title = {Zebras}, publisher = {ACME}, year = 2019}
+@article{doe2020,
+  author = {Jane Doe and Max Mustermann},
+  title = {Apples?},
+  journal = {Fruit},
+  volume = 3,
+  pages = {1--10},
+  year = 2020,
+  doi = {10.1000/1},
+}
+"###;
+    let mut options = Options {
+        bibliography: Bibliography::from_bibtex(bibtex).unwrap(),
+        ..Options::default()
+    };
+
+    let source_code = r###"
+Zebras [@roe2019, p. 3] eat apples [@doe2020; @roe2019].
+@bibliography
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>Zebras <span class="writer4-citation">(<a href="#ref-roe2019">Roe 2019</a>, p. 3)</span> eat apples <span class="writer4-citation">(<a href="#ref-doe2020">Doe and Mustermann 2020</a>; <a href="#ref-roe2019">Roe 2019</a>)</span>.</p><section class="writer4-bibliography"><ul><li id="ref-doe2020">Doe, Jane and Max Mustermann (2020). Apples? <i>Fruit</i>, 3, 1–10. <a href="https://doi.org/10.1000/1">https://doi.org/10.1000/1</a></li><li id="ref-roe2019">Roe, Richard (2019). Zebras. ACME.</li></ul></section></div>"###;
+    assert_eq!(
+        HtmlBackend::compile(source_code, &options).unwrap(),
+        expected_output
+    );
+
+    options.citation_style = CitationStyle::Numeric;
+    let expected_output = r###"<div class="writer4-doc"><p>Zebras <span class="writer4-citation">[<a href="#ref-roe2019">1</a>, p. 3]</span> eat apples <span class="writer4-citation">[<a href="#ref-doe2020">2</a>; <a href="#ref-roe2019">1</a>]</span>.</p><section class="writer4-bibliography"><ol><li id="ref-roe2019">Roe, Richard. Zebras. ACME, 2019.</li><li id="ref-doe2020">Doe, Jane and Max Mustermann. Apples? <i>Fruit</i>, 3, 1–10. 2020. <a href="https://doi.org/10.1000/1">https://doi.org/10.1000/1</a></li></ol></section></div>"###;
+    assert_eq!(
+        HtmlBackend::compile(source_code, &options).unwrap(),
+        expected_output
+    );
+
+    assert!(HtmlBackend::compile("[@unknown]", &options).is_err());
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn raw_blocks() {
+    use writer4_compiler::{Backend, HtmlBackend, Options};
+
+    let source_code = r###"
+```{=html}
+<video src="a.mp4"></video>
+```
+```{=latex}
+\newpage
+```
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><video src="a.mp4"></video>
+</div>"###;
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    let options = Options {
+        forbid_raw: true,
+        ..Options::default()
+    };
+    assert!(HtmlBackend::compile(source_code, &options).is_err());
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn comments() {
+    let source_code = r###"
+First line
+// Between lines
+second line
+- One
+/*
+- Hidden
+*/
+- Two
+| a | b |
+// Between rows
+| c | d |
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>First line<br>second line</p><ul><li>One</li><li>Two</li></ul><table><tbody><tr><td>a</td><td>b</td></tr><tr><td>c</td><td>d</td></tr></tbody></table></div>"###;
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    let source_code = r###"
+| H |
+// Between header and separator
+|---|
+| a |
+Term
+// Between term and definition
+  : Definition
+// Between definitions
+  : Another
+{.x}
+// Between attributes and paragraph
+Styled
+
+> Quoted
+// Between quoted lines
+> lines[^a]
+[^a]: Note
+// Within a footnote
+continued
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><table><thead><tr><th>H</th></tr></thead><tbody><tr><td>a</td></tr></tbody></table><dl><dt>Term</dt><dd>Definition</dd><dd>Another</dd></dl><p class="x">Styled</p><blockquote><p>Quoted<br>lines<sup class="writer4-footnote-ref"><a id="fnref-1" href="#fn-1">1</a></sup></p></blockquote><section class="writer4-footnotes"><ol><li id="fn-1">Note<br>continued <a class="writer4-footnote-backref" href="#fnref-1">↩</a></li></ol></section></div>"###;
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn conditionals() {
+    use writer4_compiler::{Backend, HtmlBackend, Options};
+
+    let source_code = r###"
+@if edition == "pro"
+# Pro features {#pro}
+- Sync
+@elif edition == "team"
+# Team features {#team}
+@else
+Upgrade to see more.
+@end
+- Export
+@if beta && !(edition == "lite")
+:::note
+@if beta
+Beta
+@end
+:::
+@end
+"###;
+
+    let mut options = Options::default();
+    options
+        .flags
+        .insert("edition".to_string(), "pro".to_string());
+    options
+        .flags
+        .insert("beta".to_string(), "false".to_string());
+    let expected_output = r###"<div class="writer4-doc"><h1 id="pro">Pro features</h1><ul><li>Sync</li><li>Export</li></ul></div>"###;
+    assert_eq!(
+        HtmlBackend::compile(source_code, &options).unwrap(),
+        expected_output
+    );
+
+    options
+        .flags
+        .insert("edition".to_string(), "lite".to_string());
+    options.flags.insert("beta".to_string(), "true".to_string());
+    let expected_output =
+        r###"<div class="writer4-doc"><p>Upgrade to see more.</p><ul><li>Export</li></ul></div>"###;
+    assert_eq!(
+        HtmlBackend::compile(source_code, &options).unwrap(),
+        expected_output
+    );
+
+    options.flags.remove("beta");
+    assert!(HtmlBackend::compile(source_code, &options).is_err());
+    assert!(HtmlBackend::compile("@if edition ==\n@end", &options).is_err());
+    assert!(HtmlBackend::compile("A\n@end", &options).is_err());
+    assert!(HtmlBackend::compile("@if edition\n@else\n@else\n@end", &options).is_err());
+    assert!(HtmlBackend::compile(&"@if edition\n".repeat(300), &options).is_err());
+
+    // Blocks inside a block quote pair up among the quoted lines
+    let source_code = r###"
+> @if edition == "lite"
+> Lite
+> @else
+> Other
+> @end
+> Quoted
+"###;
+    let expected_output =
+        r###"<div class="writer4-doc"><blockquote><p>Lite<br>Quoted</p></blockquote></div>"###;
+    assert_eq!(
+        HtmlBackend::compile(source_code, &options).unwrap(),
+        expected_output
+    );
+    assert!(HtmlBackend::compile("> @if edition\nx\n@end", &options).is_err());
+
+    let source_code = r###"
+> @if edition == "lite"
+// Between quoted lines
+> Lite
+> @end
+"###;
+    let expected_output =
+        r###"<div class="writer4-doc"><blockquote><p>Lite</p></blockquote></div>"###;
+    assert_eq!(
+        HtmlBackend::compile(source_code, &options).unwrap(),
+        expected_output
+    );
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn attributes() {
+    use writer4_compiler::{Backend, HtmlBackend, Options};
+
+    let source_code = r###"
+{.wide}
+# Title {#top .big data-note="a&b"}
+{#intro .lead}
+Some [highlighted]{.hl title="<x>"} text
+{.steps}
+- One
+  - Nested
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><h1 id="top" class="wide big" data-note="a&amp;b">Title</h1><p id="intro" class="lead">Some <span class="hl" title="&lt;x&gt;">highlighted</span> text</p><ul class="steps"><li>One<ul><li>Nested</li></ul></li></ul></div>"###;
+    assert_eq!(
+        writer4_compiler::compile_html(source_code).unwrap(),
+        expected_output
+    );
+
+    assert!(writer4_compiler::compile_html("{.x}\n\nText").is_err());
+    assert!(writer4_compiler::compile_html("Text\n{.x}").is_err());
+
+    assert_eq!(
+        writer4_compiler::compile_html("{#terms .x}\nTerm\n  : Definition").unwrap(),
+        r###"<div class="writer4-doc"><dl id="terms" class="x"><dt>Term</dt><dd>Definition</dd></dl></div>"###
+    );
+
+    // HTML doesn't allow repeating an attribute
+    assert!(writer4_compiler::compile_html("{title=a title=b}\nText").is_err());
+    assert!(writer4_compiler::compile_html("[x]{#a id=b}").is_err());
+    assert!(writer4_compiler::compile_html("{title=a}\n# Title {TITLE=b}").is_err());
+
+    let options = Options {
+        forbid_raw: true,
+        ..Options::default()
+    };
+    assert!(HtmlBackend::compile("[x]{onclick=alert()}", &options).is_err());
+    assert!(HtmlBackend::compile("[x]{STYLE=color:red}", &options).is_err());
+    assert!(HtmlBackend::compile("[x](javascript:alert())", &options).is_err());
+    assert!(HtmlBackend::compile("[x](\u{1}JavaScript:alert())", &options).is_err());
+    assert!(HtmlBackend::compile("![x](vbscript:msgbox)", &options).is_err());
+    assert!(HtmlBackend::compile("[x](https://example.com/a:b)", &options).is_ok());
+}
+
+#[test]
+#[cfg(feature = "html-backend")]
+fn directives() {
+    use std::borrow::Cow;
+    use writer4_compiler::{hir, Backend, HtmlBackend, Options};
+
+    let mut options = Options::default();
+    options.directives.register_inline("kbd", |directive| {
+        let mut segments = vec![hir::TextSegment::Raw {
+            target: "html",
+            content: Cow::Borrowed("<kbd>"),
+        }];
+        segments.extend(directive.label);
+        segments.push(hir::TextSegment::Raw {
+            target: "html",
+            content: Cow::Borrowed("</kbd>"),
+        });
+        Ok(segments)
+    });
+    options.directives.register_block("video", |directive| {
+        let src = directive
+            .arguments
+            .pairs
+            .iter()
+            .find(|(key, _)| *key == "src")
+            .ok_or("missing `src`")?
+            .1;
+        Ok(vec![hir::Statement::Raw(hir::RawStatement {
+            target: "html",
+            content: Cow::Owned(format!(r#"<video src="{}"></video>"#, src)),
+        })])
+    });
+    options.directives.register_block("details", |directive| {
+        let mut statements = vec![hir::Statement::Raw(hir::RawStatement {
+            target: "html",
+            content: Cow::Borrowed("<details>"),
+        })];
+        statements.extend(directive.body);
+        statements.push(hir::Statement::Raw(hir::RawStatement {
+            target: "html",
+            content: Cow::Borrowed("</details>"),
+        }));
+        Ok(statements)
+    });
+
+    let source_code = r###"
+Press :kbd[Ctrl **C**] to copy (or :kbd[Ctrl X]).
+Index with std::vec[0], ratio a:b{c=1} and \:kbd[C].
+::video{src=cat.mp4}
+:::details
+Hidden
+:::
+"###;
+
+    let expected_output = r###"<div class="writer4-doc"><p>Press <kbd>Ctrl <b>C</b></kbd> to copy (or <kbd>Ctrl X</kbd>).<br>Index with std::vec[0], ratio a:b{c=1} and :kbd[C].</p><video src="cat.mp4"></video><details><p>Hidden</p></details></div>"###;
+    assert_eq!(
+        HtmlBackend::compile(source_code, &options).unwrap(),
+        expected_output
+    );
+
+    assert!(HtmlBackend::compile("::video", &options).is_err());
+    assert!(HtmlBackend::compile("::unknown", &options).is_err());
+    assert!(HtmlBackend::compile(&":::details\n".repeat(300), &options).is_err());
+    assert_eq!(
+        HtmlBackend::compile(":::details\n# Inner\n:::\nSee <<inner>>.", &options).unwrap(),
+        r###"<div class="writer4-doc"><details><h1 id="inner">Inner</h1></details><p>See <a href="#inner">Inner</a>.</p></div>"###
+    );
+    assert!(HtmlBackend::compile(":::details\n{#a}\nA\n:::\n# B {#a}", &options).is_err());
+    assert!(writer4_compiler::compile_html("Press :kbd[C]").is_err());
+    assert_eq!(
+        writer4_compiler::compile_html("# Use std::vec[0]\n| a:b{c=1} |").unwrap(),
+        r###"<div class="writer4-doc"><h1 id="use-stdvec0">Use std::vec[0]</h1><table><tbody><tr><td>a:b{c=1}</td></tr></tbody></table></div>"###
+    );
+}